@@ -31,10 +31,10 @@ fn main() {
         // Calculate the position of the touch point
         let touch_y = if elapsed % cycle_duration < half_cycle {
             // Moving down
-            ((elapsed % half_cycle) / half_cycle * 942.0) as u16 // 942 is the max Y value for the touchpad
+            (elapsed % half_cycle) / half_cycle
         } else {
             // Moving up
-            (942.0 - ((elapsed % half_cycle) / half_cycle * 942.0)) as u16
+            1.0 - (elapsed % half_cycle) / half_cycle
         };
 
         let report = DS4ReportExBuilder::new()
@@ -51,7 +51,7 @@ fn main() {
             .special(DS4SpecialButtons::new().ps_home(true))
             .status(DS4Status::with_battery_status(BatteryStatus::Charging(8)))
             // Set the touch report with the calculated Y position
-            .touch_reports(Some(DS4TouchReport::new(0, Some(DS4TouchPoint::new(1920, touch_y)), None)), None, None)
+            .touch_reports(Some(DS4TouchReport::new(0, Some(DS4TouchPoint::from_normalized(1.0, touch_y as f32)), None)), None, None)
             .build();
 
         let _ = target.update_ex(&report);
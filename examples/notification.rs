@@ -32,7 +32,7 @@ fn main() {
 
 	// Handle notifications on a separate thread
 	let counter = count.clone();
-	let thread = target.request_notification().unwrap().spawn_thread(move |_, data| {
+	let (thread, _stop) = target.request_notification().unwrap().spawn_thread(move |_, data| {
 		counter.fetch_add(1, atomic::Ordering::SeqCst);
 		println!("{:#?}", data);
 	});
@@ -54,7 +54,9 @@ fn main() {
 
 	// Dropping the target causes the notification request to abort and the thread to return
 	drop(target);
-	thread.join().unwrap();
+	if let Err(err) = thread.join().unwrap() {
+		println!("Notification thread failed: {}", err);
+	}
 
 	println!("Got {} notifications!", count.load(atomic::Ordering::SeqCst));
 }
@@ -6,6 +6,8 @@ use winapi::um::ioapiset::*;
 use winapi::um::minwinbase::*;
 use winapi::um::synchapi::*;
 use winapi::um::errhandlingapi::*;
+use winapi::um::winbase::{INFINITE, WAIT_FAILED, WAIT_OBJECT_0};
+use winapi::um::winnt::MAXIMUM_WAIT_OBJECTS;
 use winapi::shared::winerror;
 use winapi::shared::ntdef::HANDLE;
 use winapi::shared::guiddef::GUID;
@@ -21,13 +23,12 @@ pub const IOCTL_PLUGIN_TARGET: u32 = 0x2AA004; //IOCTL_BASE + 0x000;
 pub const IOCTL_UNPLUG_TARGET: u32 = 0x2AA008; //IOCTL_BASE + 0x001;
 pub const IOCTL_CHECK_VERSION: u32 = 0x2AA00C; //IOCTL_BASE + 0x002;
 pub const IOCTL_WAIT_DEVICE_READY: u32 = 0x2AA010; //IOCTL_BASE + 0x003;
-#[cfg(feature = "unstable_xtarget_notification")]
 pub const IOCTL_XUSB_REQUEST_NOTIFICATION : u32 = 0x2AE804; //IOCTL_BASE + 0x200 (RW);
 pub const IOCTL_XUSB_SUBMIT_REPORT: u32 = 0x2AA808; //IOCTL_BASE + 0x201;
 pub const IOCTL_DS4_SUBMIT_REPORT: u32 = 0x2AA80C; //IOCTL_BASE + 0x202;
 pub const IOCTL_DS4_REQUEST_NOTIFICATION: u32 = 0x2AA810; //IOCTL_BASE + 0x203;
 pub const IOCTL_XUSB_GET_USER_INDEX: u32 = 0x2AE81C; //IOCTL_BASE + 0x206 (RW);
-// pub const IOCTL_DS4_AWAIT_OUTPUT_AVAILABLE: u32 = 0x2AE820; //IOCTL_BASE + 0x207 (RW);
+pub const IOCTL_DS4_AWAIT_OUTPUT_AVAILABLE: u32 = 0x2AE820; //IOCTL_BASE + 0x207 (RW);
 
 #[repr(C)]
 pub struct CheckVersion {
@@ -156,6 +157,57 @@ impl WaitDeviceReady {
 			}
 		}
 
+		Ok(())
+	}
+	// Like `ioctl` but waits at most `timeout_ms` milliseconds, returns `WAIT_TIMEOUT` if the device isn't ready in time.
+	#[inline]
+	pub unsafe fn ioctl_timeout(&mut self, device: HANDLE, event: HANDLE, timeout_ms: u32) -> Result<(), u32> {
+		match self.ioctl_timeout_raw(device, event, timeout_ms) {
+			// Version pre-1.17 where this IOCTL doesn't exist
+			Err(winerror::ERROR_INVALID_PARAMETER) => Ok(()),
+			result => result,
+		}
+	}
+	// Like `ioctl_timeout` but returns `ERROR_INVALID_PARAMETER` from pre-1.17 versions instead of ignoring it.
+	#[inline]
+	pub unsafe fn ioctl_timeout_raw(&mut self, device: HANDLE, event: HANDLE, timeout_ms: u32) -> Result<(), u32> {
+		let mut transferred = 0;
+		let mut overlapped: OVERLAPPED = mem::zeroed();
+		overlapped.hEvent = event;
+
+		let mut err = 0;
+		if DeviceIoControl(
+			device,
+			IOCTL_WAIT_DEVICE_READY,
+			self as *mut _ as _,
+			mem::size_of_val(self) as u32,
+			ptr::null_mut(),
+			0,
+			&mut transferred,
+			&mut overlapped) == 0
+		{
+			err = GetLastError();
+		}
+
+		if err == 0 || err == winerror::ERROR_IO_PENDING {
+			let wait = WaitForSingleObject(event, timeout_ms);
+			if wait != WAIT_OBJECT_0 {
+				// The request must complete before the overlapped structure goes out of scope
+				let err = if wait == winerror::WAIT_TIMEOUT { winerror::WAIT_TIMEOUT } else { GetLastError() };
+				CancelIoEx(device, &mut overlapped);
+				GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1);
+				return Err(err);
+			}
+			err = 0;
+			if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */0) == 0 {
+				err = GetLastError();
+			}
+		}
+
+		if err != 0 {
+			return Err(err);
+		}
+
 		Ok(())
 	}
 }
@@ -236,7 +288,6 @@ impl XUsbSubmitReport {
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
 #[repr(C)]
 pub struct XUsbRequestNotification {
 	pub Size: u32,
@@ -246,7 +297,6 @@ pub struct XUsbRequestNotification {
 	pub LedNumber: u8,
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
 impl XUsbRequestNotification {
 	#[inline]
 	pub const fn new(serial_no: u32) -> XUsbRequestNotification {
@@ -260,27 +310,52 @@ impl XUsbRequestNotification {
 	}
 }
 
-#[derive(Debug, Default)]
 #[repr(C)]
-pub struct DS4OutputReport {
-	pub small_motor: u8,
-	pub large_motor: u8,
-	pub lightbar_color: DS4LightbarColor,
+pub struct DS4AwaitOutput {
+	pub Size: u32,
+	pub SerialNo: u32,
+	pub Report: [u8; 64],
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-#[repr(C)]
-pub struct DS4LightbarColor {
-	pub r: u8,
-	pub g: u8,
-	pub b: u8,
+impl DS4AwaitOutput {
+	#[inline]
+	pub const fn new(serial_no: u32) -> DS4AwaitOutput {
+		DS4AwaitOutput {
+			Size: mem::size_of::<DS4AwaitOutput>() as u32,
+			SerialNo: serial_no,
+			Report: [0; 64],
+		}
+	}
+	// Blocks until the game writes the next output report, requires driver version 1.17 or later.
+	#[inline]
+	pub unsafe fn ioctl(&mut self, device: HANDLE, event: HANDLE) -> Result<(), u32> {
+		let mut transferred = 0;
+		let mut overlapped: OVERLAPPED = mem::zeroed();
+		overlapped.hEvent = event;
+
+		DeviceIoControl(
+			device,
+			IOCTL_DS4_AWAIT_OUTPUT_AVAILABLE,
+			self as *mut _ as _,
+			mem::size_of_val(self) as u32,
+			self as *mut _ as _,
+			mem::size_of_val(self) as u32,
+			&mut transferred,
+			&mut overlapped);
+
+		if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
+			return Err(GetLastError());
+		}
+
+		Ok(())
+	}
 }
 
 #[repr(C)]
 pub struct DS4RequestNotification {
 	pub Size: u32,
 	pub SerialNo: u32,
-	pub Report: DS4OutputReport,
+	pub Report: crate::DS4OutputReport,
 }
 
 impl DS4RequestNotification {
@@ -289,7 +364,7 @@ impl DS4RequestNotification {
 		DS4RequestNotification {
 			Size: mem::size_of::<DS4RequestNotification>() as u32,
 			SerialNo: serial_no,
-			Report: DS4OutputReport::default(),
+			Report: crate::DS4OutputReport::default(),
 		}
 	}
 }
@@ -298,12 +373,13 @@ impl DS4RequestNotification {
 pub struct RequestNotification {
 	pub overlapped: OVERLAPPED,
 	pub buffer: RequestNotificationVariant,
+	// Error of the last request if it failed to start, its event is never signaled
+	start_error: u32,
 }
 
 #[repr(C)]
 pub enum RequestNotificationVariant {
-	#[cfg(feature = "unstable_xtarget_notification")]
-	XUsb(XUsbRequestNotification),
+	X360(XUsbRequestNotification),
 	DS4(DS4RequestNotification),
 }
 // Safety: This instance must have a stable address (eg. on the heap)
@@ -313,15 +389,30 @@ impl RequestNotification {
 	pub fn new(buffer: RequestNotificationVariant) -> RequestNotification {
 		let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
 		overlapped.hEvent = unsafe { CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
-		RequestNotification { overlapped, buffer }
+		RequestNotification { overlapped, buffer, start_error: 0 }
+	}
+	// Serial number of the target, regardless of the variant
+	#[inline]
+	pub fn serial_no(&self) -> u32 {
+		match &self.buffer {
+			RequestNotificationVariant::X360(buffer) => buffer.SerialNo,
+			RequestNotificationVariant::DS4(buffer) => buffer.SerialNo,
+		}
+	}
+	// Marks the target as gone, no more notifications are requested
+	#[inline]
+	pub fn clear_serial_no(&mut self) {
+		match &mut self.buffer {
+			RequestNotificationVariant::X360(buffer) => buffer.SerialNo = 0,
+			RequestNotificationVariant::DS4(buffer) => buffer.SerialNo = 0,
+		}
 	}
 	#[inline]
 	pub unsafe fn ioctl(&mut self, device: HANDLE) {
 		let mut transferred = 0;
 		
-		match &mut self.buffer {
-			#[cfg(feature = "unstable_xtarget_notification")]
-			RequestNotificationVariant::XUsb(ref mut buffer) => {
+		let success = match &mut self.buffer {
+			RequestNotificationVariant::X360(ref mut buffer) => {
 				let buffer_ptr = buffer as *mut _ as _;
 				let buffer_size = mem::size_of::<XUsbRequestNotification>() as u32;
 				DeviceIoControl(
@@ -333,7 +424,7 @@ impl RequestNotification {
 					buffer_size,
 					&mut transferred,
 					&mut self.overlapped
-				);
+				)
 			},
 			RequestNotificationVariant::DS4(ref mut buffer) => {
 				let buffer_ptr = buffer as *mut _ as _;
@@ -347,38 +438,92 @@ impl RequestNotification {
 					buffer_size,
 					&mut transferred,
 					&mut self.overlapped
-				);
+				)
 			},
 		};
+
+		// A pending request is the expected outcome, completed requests signal the event as well
+		self.start_error = match success {
+			0 => match GetLastError() {
+				winerror::ERROR_IO_PENDING => 0,
+				err => err,
+			},
+			_ => 0,
+		};
 	}
+	// Cancels the request and waits for it to finish.
+	// Returns true if the request completed successfully before it could be cancelled, the buffer then holds its notification.
 	#[inline]
-	pub unsafe fn cancel(&mut self, device: HANDLE) -> Result<(), u32> {
+	pub unsafe fn cancel(&mut self, device: HANDLE) -> Result<bool, u32> {
+		// A request which never started is not pending
+		if self.start_error != 0 {
+			return Ok(false);
+		}
+		let mut not_found = false;
+		let mut cancel_error = None;
 		if CancelIoEx(device, &mut self.overlapped) == 0 {
-			let err = GetLastError();
-			// If no pending IO then everything is fine
-			if err == winerror::ERROR_NOT_FOUND {
-				return Ok(());
+			match GetLastError() {
+				// If no pending IO the request already completed, its result is retrieved below
+				winerror::ERROR_NOT_FOUND => not_found = true,
+				err => cancel_error = Some(err),
 			}
-			return Err(err);
 		}
+		// Even if cancelling failed the request must complete before the overlapped structure may be freed
 		let mut transferred = 0;
-		if GetOverlappedResult(device, &mut self.overlapped, &mut transferred, /*bWait: */1) == 0 {
-			let err = GetLastError();
-			// Expect the operation to be aborted
-			if err != winerror::ERROR_OPERATION_ABORTED {
-				return Err(err);
-			}
+		let wait_error = match GetOverlappedResult(device, &mut self.overlapped, &mut transferred, /*bWait: */1) {
+			0 => GetLastError(),
+			_ => 0,
+		};
+		if let Some(err) = cancel_error {
+			return Err(err);
+		}
+		match wait_error {
+			0 => Ok(true),
+			// Expect the operation to be aborted, requests which already failed on their own are not an error of cancelling
+			winerror::ERROR_OPERATION_ABORTED => Ok(false),
+			_ if not_found => Ok(false),
+			err => Err(err),
 		}
-		Ok(())
 	}
 	#[inline]
 	pub unsafe fn poll(&mut self, device: HANDLE, wait: bool) -> Result<(), u32> {
+		// Waiting for a request which never started would block forever
+		if self.start_error != 0 {
+			return Err(self.start_error);
+		}
 		let mut transferred = 0;
 		if GetOverlappedResult(device, &mut self.overlapped, &mut transferred, wait as i32) == 0 {
 			return Err(GetLastError());
 		}
 		Ok(())
 	}
+	// Waits at most `timeout_ms` milliseconds for the request to complete.
+	// Returns `ERROR_IO_INCOMPLETE` if the request is still pending after the timeout, like a non-blocking `poll`.
+	#[inline]
+	pub unsafe fn poll_timeout(&mut self, device: HANDLE, timeout_ms: u32) -> Result<(), u32> {
+		if self.start_error != 0 {
+			return Err(self.start_error);
+		}
+		match WaitForSingleObject(self.overlapped.hEvent, timeout_ms) {
+			WAIT_OBJECT_0 => self.poll(device, false),
+			winerror::WAIT_TIMEOUT => Err(winerror::ERROR_IO_INCOMPLETE),
+			_ => Err(GetLastError()),
+		}
+	}
+	// Cancels the pending request without waiting for it to complete.
+	// Unlike `cancel` this may be called from any thread, the owner observes the abort when it polls.
+	#[inline]
+	pub unsafe fn cancel_async(device: HANDLE, overlapped: *mut OVERLAPPED) -> Result<(), u32> {
+		if CancelIoEx(device, overlapped) == 0 {
+			let err = GetLastError();
+			// If no pending IO then everything is fine
+			if err == winerror::ERROR_NOT_FOUND {
+				return Ok(());
+			}
+			return Err(err);
+		}
+		Ok(())
+	}
 }
 impl Drop for RequestNotification {
 	fn drop(&mut self) {
@@ -425,6 +570,137 @@ impl DS4SubmitReport {
 	}
 }
 
+// DS4 submit report which completes in the background.
+// Safety: This instance must have a stable address (eg. on the heap) while the request is pending.
+pub struct DS4SubmitReportAsync {
+	pub overlapped: OVERLAPPED,
+	pub request: DS4SubmitReport,
+	// Error of the last request if it failed to start, its event is never signaled
+	start_error: u32,
+}
+impl DS4SubmitReportAsync {
+	#[inline]
+	pub fn new() -> DS4SubmitReportAsync {
+		let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+		overlapped.hEvent = unsafe { CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
+		let request = DS4SubmitReport::new(0, crate::DS4Report::NEUTRAL);
+		DS4SubmitReportAsync { overlapped, request, start_error: 0 }
+	}
+	// Starts the request without waiting for it to complete.
+	#[inline]
+	pub unsafe fn start(&mut self, device: HANDLE) -> Result<(), u32> {
+		let mut transferred = 0;
+		let success = DeviceIoControl(
+			device,
+			IOCTL_DS4_SUBMIT_REPORT,
+			&mut self.request as *mut _ as _,
+			mem::size_of_val(&self.request) as u32,
+			ptr::null_mut(),
+			0,
+			&mut transferred,
+			&mut self.overlapped);
+
+		// A pending request is the expected outcome, completed requests signal the event as well
+		self.start_error = match success {
+			0 => match GetLastError() {
+				winerror::ERROR_IO_PENDING => 0,
+				err => err,
+			},
+			_ => 0,
+		};
+		match self.start_error {
+			0 => Ok(()),
+			err => Err(err),
+		}
+	}
+	// Returns `ERROR_IO_INCOMPLETE` if `wait` is false and the request is still pending.
+	#[inline]
+	pub unsafe fn poll(&mut self, device: HANDLE, wait: bool) -> Result<(), u32> {
+		// Waiting for a request which never started would block forever
+		if self.start_error != 0 {
+			return Err(self.start_error);
+		}
+		let mut transferred = 0;
+		if GetOverlappedResult(device, &mut self.overlapped, &mut transferred, wait as i32) == 0 {
+			return Err(GetLastError());
+		}
+		Ok(())
+	}
+}
+impl Drop for DS4SubmitReportAsync {
+	fn drop(&mut self) {
+		unsafe { CloseHandle(self.overlapped.hEvent); }
+	}
+}
+// Safety: the event handle and the request buffer are not tied to the thread which created them
+unsafe impl Send for DS4SubmitReportAsync {}
+
+pub struct DS4SubmitBatchEntry {
+	pub device: HANDLE,
+	pub event: HANDLE,
+	pub report: DS4SubmitReport,
+}
+
+// Submits all the reports before waiting for them together.
+// Returns the result of every entry, all submitted reports are waited for regardless.
+#[inline]
+pub unsafe fn ds4_submit_batch(entries: &mut [DS4SubmitBatchEntry]) -> Vec<Result<(), u32>> {
+	debug_assert!(entries.len() <= MAXIMUM_WAIT_OBJECTS as usize);
+
+	let mut results = vec![Ok(()); entries.len()];
+	let mut overlapped: Vec<OVERLAPPED> = entries.iter().map(|entry| {
+		let mut overlapped: OVERLAPPED = mem::zeroed();
+		overlapped.hEvent = entry.event;
+		overlapped
+	}).collect();
+	let mut started = Vec::with_capacity(entries.len());
+
+	for (index, entry) in entries.iter_mut().enumerate() {
+		let mut transferred = 0;
+		if DeviceIoControl(
+			entry.device,
+			IOCTL_DS4_SUBMIT_REPORT,
+			&mut entry.report as *mut _ as _,
+			mem::size_of_val(&entry.report) as u32,
+			ptr::null_mut(),
+			0,
+			&mut transferred,
+			&mut overlapped[index]) == 0
+		{
+			let err = GetLastError();
+			// The event is never signaled for requests which failed to start
+			if err != winerror::ERROR_IO_PENDING {
+				results[index] = Err(err);
+				continue;
+			}
+		}
+		started.push(index);
+	}
+
+	let mut wait_error = None;
+	if !started.is_empty() {
+		let events: Vec<HANDLE> = started.iter().map(|&index| entries[index].event).collect();
+		if WaitForMultipleObjects(events.len() as u32, events.as_ptr(), /*bWaitAll: */1, INFINITE) == WAIT_FAILED {
+			wait_error = Some(GetLastError());
+			// The requests must not outlive their overlapped structures, cancel them and wait below
+			for &index in &started {
+				CancelIoEx(entries[index].device, &mut overlapped[index]);
+			}
+		}
+	}
+
+	// Returns immediately for the requests which completed in the wait above
+	for &index in &started {
+		let mut transferred = 0;
+		if GetOverlappedResult(entries[index].device, &mut overlapped[index], &mut transferred, /*bWait: */1) == 0 {
+			// Requests cancelled because the wait failed report why the wait failed
+			results[index] = Err(wait_error.unwrap_or_else(|| GetLastError()));
+		}
+	}
+
+	results
+}
+
 #[repr(C, packed)]
 pub struct DS4SubmitReportEx {
 	pub Size: u32,
@@ -510,3 +786,27 @@ impl XUsbGetUserIndex {
 		Ok(())
 	}
 }
+
+// Issues an arbitrary request, returning the number of bytes written to the output buffer.
+#[inline]
+pub unsafe fn raw_ioctl(device: HANDLE, event: HANDLE, code: u32, input: &[u8], output: &mut [u8]) -> Result<usize, u32> {
+	let mut transferred = 0;
+	let mut overlapped: OVERLAPPED = mem::zeroed();
+	overlapped.hEvent = event;
+
+	DeviceIoControl(
+		device,
+		code,
+		input.as_ptr() as *mut _,
+		input.len() as u32,
+		output.as_mut_ptr() as _,
+		output.len() as u32,
+		&mut transferred,
+		&mut overlapped);
+
+	if GetOverlappedResult(device, &mut overlapped, &mut transferred, /*bWait: */1) == 0 {
+		return Err(GetLastError());
+	}
+
+	Ok(transferred as usize)
+}
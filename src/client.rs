@@ -1,23 +1,139 @@
-use std::{mem, ptr};
+use std::{cmp, mem, ptr, thread, time};
+use std::collections::HashSet;
+use std::sync::{atomic, Arc, Mutex, PoisonError};
 use std::os::windows::io as win_io;
+#[cfg(feature = "install_hint")]
+use std::fmt;
 use winapi::um::handleapi::*;
 use winapi::um::setupapi::*;
 use winapi::um::fileapi::*;
 use winapi::um::winnt::*;
 use winapi::um::winbase::*;
 use winapi::um::errhandlingapi::*;
+use winapi::um::winreg::*;
+use winapi::shared::minwindef::HKEY;
+use winapi::shared::winerror;
 use winapi::shared::ntdef::HANDLE;
 use crate::*;
 
+/// Kind of a virtual controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TargetKind {
+	/// A wired Xbox360 controller, see [`Xbox360Wired`].
+	Xbox360Wired,
+	/// A wired DualShock4 controller, see [`DualShock4Wired`].
+	DualShock4Wired,
+}
+
+/// A virtual controller found by [`Client::enumerate_targets`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TargetInfo {
+	/// The serial number the target is plugged in with.
+	pub serial_no: u32,
+	/// The kind of the target.
+	pub kind: TargetKind,
+}
+
+/// Options for opening the ViGEmBus device, see [`Client::connect_with`].
+///
+/// The access and share mode are passed as `dwDesiredAccess` and `dwShareMode` to `CreateFileW`.
+/// The device is always opened for overlapped I/O, the flags are not configurable.
+///
+/// The defaults are the ones used by [`Client::connect`]:
+/// `GENERIC_READ | GENERIC_WRITE` access and `FILE_SHARE_READ | FILE_SHARE_WRITE` share mode.
+/// The default share mode already allows any number of clients in the same or other processes.
+///
+/// The driver checks the access of the handle for every request:
+///
+/// * `GENERIC_WRITE` (or `FILE_WRITE_DATA`) is required for all requests.
+///   Without it the version check is rejected and connecting returns [`Error::BusVersionMismatch`].
+/// * `GENERIC_READ` (or `FILE_READ_DATA`) is additionally required for Xbox360 notifications, [`Xbox360Wired::get_user_index`]
+///   and waiting for DualShock4 output. Without it these return `WinError` with `ERROR_ACCESS_DENIED`.
+///
+/// A narrower share mode does not give this client exclusive use of the bus.
+/// Whether other clients opening the bus fail with [`Error::BusAccessFailed`] (`ERROR_SHARING_VIOLATION`) is up to the driver,
+/// do not rely on it to keep other applications away.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ConnectOptions {
+	access: u32,
+	share_mode: u32,
+}
+
+impl Default for ConnectOptions {
+	#[inline]
+	fn default() -> ConnectOptions {
+		ConnectOptions {
+			access: GENERIC_READ | GENERIC_WRITE,
+			share_mode: FILE_SHARE_READ | FILE_SHARE_WRITE,
+		}
+	}
+}
+
+impl ConnectOptions {
+	/// Creates the default options.
+	#[inline]
+	pub fn new() -> ConnectOptions {
+		ConnectOptions::default()
+	}
+
+	/// Sets the desired access, `dwDesiredAccess` of `CreateFileW`.
+	#[inline]
+	pub fn access(mut self, access: u32) -> ConnectOptions {
+		self.access = access;
+		self
+	}
+
+	/// Sets the share mode, `dwShareMode` of `CreateFileW`.
+	#[inline]
+	pub fn share_mode(mut self, share_mode: u32) -> ConnectOptions {
+		self.share_mode = share_mode;
+		self
+	}
+}
+
 /// The ViGEmBus service connection.
 #[derive(Debug)]
 pub struct Client {
 	pub(crate) device: HANDLE,
+	// Serial number likely to be free, where the next plugin scan starts
+	free_slot: atomic::AtomicU32,
+	pub(crate) shared_event: Option<Arc<SharedEvent>>,
+	// Ids of the plugged in targets when duplicates are rejected
+	plugged_ids: Option<Arc<Mutex<HashSet<TargetId>>>>,
+	// Options the device was opened with, used again by `reconnect`
+	options: ConnectOptions,
 }
 
 impl Client {
 	/// Connects to the ViGEmBus service.
 	pub fn connect() -> Result<Client, Error> {
+		Client::connect_with(ConnectOptions::default())
+	}
+
+	/// Connects to the ViGEmBus service, opening the device with the given options.
+	///
+	/// Use [`connect`](Self::connect) unless the handle needs a different access or share mode,
+	/// see [`ConnectOptions`] for the combinations accepted by the driver.
+	///
+	/// ```no_run
+	/// use winapi::um::winnt::{GENERIC_READ, GENERIC_WRITE};
+	///
+	/// let options = vigem_client::ConnectOptions::new()
+	///     .access(GENERIC_READ | GENERIC_WRITE)
+	///     .share_mode(0);
+	/// let client = vigem_client::Client::connect_with(options).unwrap();
+	/// ```
+	pub fn connect_with(options: ConnectOptions) -> Result<Client, Error> {
+		let result = Client::open_bus(&options);
+		match &result {
+			Ok(_) => log_debug!("connected to ViGEmBus"),
+			Err(err) => log_warn!("connecting to ViGEmBus failed: {}", err),
+		}
+		result
+	}
+
+	fn open_bus(options: &ConnectOptions) -> Result<Client, Error> {
 		unsafe {
 			let mut error = Error::BusNotFound;
 
@@ -34,7 +150,7 @@ impl Client {
 				DIGCF_PRESENT | DIGCF_DEVICEINTERFACE);
 
 			if device_info_set == INVALID_HANDLE_VALUE {
-				return Err(Error::WinError(GetLastError()));
+				return Err(Error::win(GetLastError(), Operation::Other));
 			}
 
 			// Enumerate device instances
@@ -62,7 +178,7 @@ impl Client {
 					&mut required_size,
 					ptr::null_mut()) == 0
 				{
-					error = Error::WinError(GetLastError());
+					error = Error::win(GetLastError(), Operation::Other);
 					continue;
 				}
 
@@ -70,22 +186,28 @@ impl Client {
 				let device_path = ptr::addr_of!((*detail_data_ptr).DevicePath) as *const u16;
 				let device = CreateFileW(
 					device_path,
-					GENERIC_READ | GENERIC_WRITE,
-					FILE_SHARE_READ | FILE_SHARE_WRITE,
+					options.access,
+					options.share_mode,
 					ptr::null_mut(),
 					OPEN_EXISTING,
 					FILE_ATTRIBUTE_NORMAL | FILE_FLAG_NO_BUFFERING | FILE_FLAG_WRITE_THROUGH | FILE_FLAG_OVERLAPPED,
 					ptr::null_mut());
 
 				if device == INVALID_HANDLE_VALUE {
-					error = Error::BusAccessFailed(GetLastError());
+					error = match GetLastError() {
+						// The device was removed after it was enumerated
+						winerror::ERROR_FILE_NOT_FOUND | winerror::ERROR_PATH_NOT_FOUND => Error::BusNotFound,
+						err => Error::BusAccessFailed(err),
+					};
 					continue;
 				}
 
 				let mut check_version = bus::CheckVersion::common();
 				if check_version.ioctl(device) {
 					SetupDiDestroyDeviceInfoList(device_info_set);
-					return Ok(Client { device })
+					let mut client = Client::from_raw_handle(device);
+					client.options = *options;
+					return Ok(client)
 				}
 
 				// version mismatch, look for another instance
@@ -98,7 +220,330 @@ impl Client {
 		}
 	}
 
+	/// Connects to the ViGEmBus service, retrying until `timeout` has elapsed.
+	///
+	/// Useful for services which start before the driver is ready.
+	/// Retries with a short backoff while the bus is not found or cannot be opened,
+	/// other errors are returned immediately.
+	///
+	/// Returns [`Error::Timeout`] if the bus could not be opened in time.
+	pub fn connect_timeout(timeout: time::Duration) -> Result<Client, Error> {
+		const MAX_BACKOFF: time::Duration = time::Duration::from_millis(500);
+
+		let start = time::Instant::now();
+		let mut backoff = time::Duration::from_millis(10);
+		loop {
+			match Client::connect() {
+				Err(Error::BusNotFound) | Err(Error::BusAccessFailed(_)) => {},
+				result => return result,
+			}
+
+			let elapsed = start.elapsed();
+			if elapsed >= timeout {
+				return Err(Error::Timeout);
+			}
+			thread::sleep(cmp::min(backoff, timeout - elapsed));
+			backoff = cmp::min(backoff * 2, MAX_BACKOFF);
+		}
+	}
+
+	/// Reconnects to the ViGEmBus service in place.
+	///
+	/// Use this after the driver was restarted, see [`Error::DriverRestarted`].
+	/// The old handle is closed and any targets created with it must be plugged in again.
+	///
+	/// The device is opened again with the [`ConnectOptions`] this client was connected with.
+	/// [Shared event mode](Self::shared_event_mode) and [rejecting duplicate ids](Self::reject_duplicate_ids) stay enabled,
+	/// the registry of plugged in ids starts out empty as the driver restart removed all targets.
+	/// A client wrapping a handle with [`from_raw_handle`](Self::from_raw_handle) reconnects with the default options.
+	pub fn reconnect(&mut self) -> Result<(), Error> {
+		let mut client = Client::connect_with(self.options)?;
+		client.shared_event = self.shared_event.clone();
+		if self.plugged_ids.is_some() {
+			client = client.reject_duplicate_ids();
+		}
+		*self = client;
+		Ok(())
+	}
+
+	/// Returns if the ViGEmBus service handle is still usable.
+	///
+	/// Returns false after the driver was restarted.
+	#[inline]
+	pub fn is_valid(&self) -> bool {
+		let mut check_version = bus::CheckVersion::common();
+		unsafe { check_version.ioctl(self.device) }
+	}
+
+	/// Sends an arbitrary request to the ViGEmBus driver.
+	///
+	/// An escape hatch for driver requests the crate does not wrap yet.
+	/// `code` is the IO control code, `input` is passed as the input buffer and `output` receives the driver's response.
+	/// Blocks until the request completes and returns the number of bytes written to `output`.
+	///
+	/// Returns `WinError` with [`Operation::Other`] if the driver fails the request.
+	///
+	/// # Safety
+	///
+	/// The driver trusts the contents of the buffers, the layout of `input` and `output` must match what the driver expects for `code`.
+	/// Requests which change the bus state, eg. plugging in or unplugging targets, are not tracked by this client or its targets.
+	/// Misuse can corrupt the state of the driver and of any targets, crash the driver or the system.
+	///
+	/// ```no_run
+	/// // IOCTL_CHECK_VERSION with the size and version of the request
+	/// const IOCTL_CHECK_VERSION: u32 = 0x2AA00C;
+	/// let mut input = [0u8; 8];
+	/// input[..4].copy_from_slice(&8u32.to_le_bytes());
+	/// input[4..].copy_from_slice(&0x0001u32.to_le_bytes());
+	///
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let result = unsafe { client.raw_ioctl(IOCTL_CHECK_VERSION, &input, &mut []) };
+	/// println!("{:?}", result);
+	/// ```
+	#[inline(never)]
+	pub unsafe fn raw_ioctl(&self, code: u32, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+		let event = Event::new(false, false);
+		bus::raw_ioctl(self.device, event.handle, code, input, output).map_err(|err| Error::win(err, Operation::Other))
+	}
+
+	/// Checks if a target with the given id can be plugged in, without plugging one in.
+	///
+	/// ViGEmBus cannot be asked whether a plugin request would succeed, and probing by plugging a target in and out again
+	/// would be seen by every application enumerating controllers. This is a best-effort check instead:
+	///
+	/// * Returns [`Error::DriverRestarted`] if the bus no longer accepts requests, see [`is_valid`](Self::is_valid).
+	/// * Returns `Ok(false)` if [`reject_duplicate_ids`](Self::reject_duplicate_ids) is enabled and a target with the id is plugged in.
+	/// * Returns `Ok(true)` otherwise.
+	///
+	/// The driver has room for tens of thousands of targets, a full bus is not detected.
+	/// `Ok(true)` is not a guarantee, the plugin may still fail eg. when another thread takes the id first.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let enabled = client.can_plugin(vigem_client::TargetId::DUALSHOCK4_WIRED).unwrap_or(false);
+	/// ```
+	#[inline(never)]
+	pub fn can_plugin(&self, id: TargetId) -> Result<bool, Error> {
+		if !self.is_valid() {
+			return Err(Error::DriverRestarted);
+		}
+		Ok(match &self.plugged_ids {
+			Some(plugged_ids) => !plugged_ids.lock().unwrap_or_else(PoisonError::into_inner).contains(&id),
+			None => true,
+		})
+	}
+
+	/// Unplugs all targets owned by this process in a single request.
+	///
+	/// The driver treats an unplug request for serial number `0` as a request to remove every target plugged in by the caller.
+	/// This is a safety net for cleanup and test teardown, targets which are still alive keep their serial number
+	/// and return an error when unplugged again, which their destructor ignores.
+	#[inline(never)]
+	pub fn unplug_all(&self) -> Result<(), Error> {
+		let event = Event::new(false, false);
+		let mut unplug = bus::UnplugTarget::new(0);
+		let result = unsafe { unplug.ioctl(self.device, event.handle) }.map_err(|err| Error::win(err, Operation::Unplug));
+		if let Err(err) = result {
+			log_warn!("unplugging all targets failed: {}", err);
+			return Err(err);
+		}
+		log_debug!("unplugged all targets");
+		self.free_slot.store(1, atomic::Ordering::Relaxed);
+		if let Some(plugged_ids) = &self.plugged_ids {
+			plugged_ids.lock().unwrap_or_else(PoisonError::into_inner).clear();
+		}
+		Ok(())
+	}
+
+	/// Returns the virtual controllers currently plugged in on the bus, by any process.
+	///
+	/// The driver has no enumeration request, instead every serial number is probed with a readiness request
+	/// which the driver answers with an error for serial numbers without a target.
+	/// Targets answering the Xbox360 user index request are reported as [`TargetKind::Xbox360Wired`],
+	/// targets the driver reports as being of another type as [`TargetKind::DualShock4Wired`].
+	///
+	/// This costs one request per serial number up to 65535, plus one per target found, and takes a noticeable amount of time.
+	/// Meant for reclaiming or cleaning up orphaned targets on startup, not for frequent polling.
+	///
+	/// Returns [`Error::Unsupported`] if the driver is older than version 1.17 which cannot answer readiness requests.
+	/// Any other failing request, eg. after a driver restart, stops the enumeration and returns `WinError`.
+	pub fn enumerate_targets(&self) -> Result<Vec<TargetInfo>, Error> {
+		let event = Event::new(false, false);
+		let mut targets = Vec::new();
+		for serial_no in 1..=SLOT_MAX {
+			if !self.probe_serial(&event, serial_no)? {
+				continue;
+			}
+			let mut gui = bus::XUsbGetUserIndex::new(serial_no);
+			let kind = match unsafe { gui.ioctl(self.device, event.handle) } {
+				Ok(()) | Err(winerror::ERROR_INVALID_DEVICE_OBJECT_PARAMETER) => TargetKind::Xbox360Wired,
+				// ViGEmBus answers STATUS_NOT_SUPPORTED for targets of another type
+				Err(winerror::ERROR_NOT_SUPPORTED) => TargetKind::DualShock4Wired,
+				Err(err) => return Err(Error::win(err, Operation::Other)),
+			};
+			targets.push(TargetInfo { serial_no, kind });
+		}
+		Ok(targets)
+	}
+
+	/// Returns the serial numbers currently used by a target on the bus, by any process, in ascending order.
+	///
+	/// Like [`enumerate_targets`](Self::enumerate_targets) every serial number is probed with a readiness request,
+	/// without querying the kind of the targets found.
+	/// This costs one request per serial number up to 65535 and takes a noticeable amount of time.
+	/// Useful for diagnosing [`Error::NoFreeSlot`], not for frequent polling.
+	///
+	/// Returns [`Error::Unsupported`] if the driver is older than version 1.17 which cannot answer readiness requests.
+	/// Any other failing request, eg. after a driver restart, stops the probing and returns `WinError`.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let used = client.used_serials().unwrap();
+	/// println!("{} of 65535 serial numbers in use", used.len());
+	/// ```
+	pub fn used_serials(&self) -> Result<Vec<u32>, Error> {
+		let event = Event::new(false, false);
+		let mut serials = Vec::new();
+		for serial_no in 1..=SLOT_MAX {
+			if self.probe_serial(&event, serial_no)? {
+				serials.push(serial_no);
+			}
+		}
+		Ok(serials)
+	}
+
+	// Returns if a target is plugged in with the serial number, without waiting for it to become ready.
+	#[inline]
+	fn probe_serial(&self, event: &Event, serial_no: u32) -> Result<bool, Error> {
+		let mut wait = bus::WaitDeviceReady::new(serial_no);
+		match unsafe { wait.ioctl_timeout_raw(self.device, event.handle, 0) } {
+			// A target which is not yet ready still exists
+			Ok(()) | Err(winerror::WAIT_TIMEOUT) => Ok(true),
+			Err(winerror::ERROR_INVALID_PARAMETER) => Err(Error::Unsupported),
+			// ViGEmBus answers STATUS_DEVICE_DOES_NOT_EXIST for serial numbers without a target
+			Err(winerror::ERROR_DEV_NOT_EXIST) => Ok(false),
+			Err(err) => Err(Error::win(err, Operation::WaitReady)),
+		}
+	}
+
+	/// Returns the version of the installed ViGEmBus driver.
+	///
+	/// The bus does not answer version queries, instead the version is read from the driver's registry key
+	/// of the first ViGEmBus device found, as major, minor, build and revision components.
+	///
+	/// Returns [`Error::Unsupported`] if the driver version is not available.
+	pub fn driver_version(&self) -> Result<(u16, u16, u16, u16), Error> {
+		unsafe {
+			let device_info_set = SetupDiGetClassDevsW(
+				&bus::GUID_DEVINTERFACE,
+				ptr::null(),
+				ptr::null_mut(),
+				DIGCF_PRESENT | DIGCF_DEVICEINTERFACE);
+
+			if device_info_set == INVALID_HANDLE_VALUE {
+				return Err(Error::win(GetLastError(), Operation::Other));
+			}
+
+			let mut device_info_data: SP_DEVINFO_DATA = mem::zeroed();
+			device_info_data.cbSize = mem::size_of_val(&device_info_data) as u32;
+
+			let result = if SetupDiEnumDeviceInfo(device_info_set, 0, &mut device_info_data) == 0 {
+				Err(Error::BusNotFound)
+			}
+			else {
+				let key = SetupDiOpenDevRegKey(device_info_set, &mut device_info_data, DICS_FLAG_GLOBAL, 0, DIREG_DRV, KEY_READ);
+				if key == INVALID_HANDLE_VALUE as HKEY {
+					Err(Error::Unsupported)
+				}
+				else {
+					let name: Vec<u16> = "DriverVersion\0".encode_utf16().collect();
+					let mut buffer = [0u16; 64];
+					let mut size = mem::size_of_val(&buffer) as u32;
+					let mut value_type = 0;
+					let status = RegQueryValueExW(key, name.as_ptr(), ptr::null_mut(), &mut value_type, buffer.as_mut_ptr() as *mut u8, &mut size);
+					RegCloseKey(key);
+
+					if status != winerror::ERROR_SUCCESS as i32 || value_type != REG_SZ {
+						Err(Error::Unsupported)
+					}
+					else {
+						let version = String::from_utf16_lossy(&buffer[..size as usize / 2]);
+						parse_version(version.trim_end_matches('\0')).ok_or(Error::Unsupported)
+					}
+				}
+			};
+
+			SetupDiDestroyDeviceInfoList(device_info_set);
+			result
+		}
+	}
+
+	/// Makes the targets created with this client share a single event for their requests.
+	///
+	/// By default every target creates its own event, a kernel object, to wait for the completion of its requests.
+	/// In shared event mode the targets use the client's event instead, which keeps the handle count constant
+	/// when managing hundreds of targets.
+	///
+	/// The event can only be used by one request at a time, so the requests of all the targets are serialized:
+	///
+	/// * Updating targets from multiple threads no longer happens in parallel, each update waits for the one in progress.
+	/// * A blocking request, eg. [`wait_ready`](DualShock4Wired::wait_ready), holds up the requests of all the other targets until it returns.
+	/// * [`DualShock4Wired::update_batch`] submits the reports of these targets one after the other.
+	/// * The handle returned by `event_handle` is the shared event and cannot tell which target's request completed.
+	///
+	/// Notification requests and clients duplicated with [`try_clone`](Self::try_clone) are not affected,
+	/// notifications have their own event and the duplicated client shares the event of this client.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap().shared_event_mode();
+	/// let targets: Vec<_> = (0..100)
+	///     .map(|_| vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED))
+	///     .collect();
+	/// ```
+	#[inline]
+	pub fn shared_event_mode(mut self) -> Client {
+		if self.shared_event.is_none() {
+			self.shared_event = Some(Arc::new(SharedEvent::new()));
+		}
+		self
+	}
+
+	/// Returns if the targets created with this client share a single event, see [`shared_event_mode`](Self::shared_event_mode).
+	#[inline]
+	pub fn is_shared_event_mode(&self) -> bool {
+		self.shared_event.is_some()
+	}
+
+	/// Makes plugging in a target fail while another target with the same [`TargetId`] is plugged in by this client.
+	///
+	/// Plugging in the same kind of controller twice is usually a logic bug which confuses games,
+	/// with this check `plugin` returns [`Error::DuplicateTargetId`] instead.
+	/// The check is opt-in, setups with intentionally identical controllers should not enable it.
+	///
+	/// Only targets plugged in through this client and its [clones](Self::try_clone) are considered,
+	/// targets of other clients or processes are not known to the client.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap().reject_duplicate_ids();
+	/// let mut first = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// let mut second = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// first.plugin().unwrap();
+	/// assert_eq!(second.plugin(), Err(vigem_client::Error::DuplicateTargetId));
+	/// ```
+	#[inline]
+	pub fn reject_duplicate_ids(mut self) -> Client {
+		if self.plugged_ids.is_none() {
+			self.plugged_ids = Some(Arc::new(Mutex::new(HashSet::new())));
+		}
+		self
+	}
+
 	/// Duplicates the ViGEmBus service handle.
+	///
+	/// The clone refers to the same bus connection and may be used from another thread or handed to another owner.
+	/// Notification requests use a clone so they do not depend on the lifetime of the original client.
+	///
+	/// Returns [`Error::DuplicateHandleFailed`] with the system error code if `DuplicateHandle` fails.
 	#[inline]
 	pub fn try_clone(&self) -> Result<Client, Error> {
 		unsafe {
@@ -110,13 +555,218 @@ impl Client {
 				GENERIC_READ | GENERIC_WRITE, 0, DUPLICATE_SAME_ACCESS);
 			if success == 0 {
 				let err = GetLastError();
-				return Err(Error::WinError(err));
+				return Err(Error::DuplicateHandleFailed(err));
 			}
-			Ok(Client { device: target_handle.assume_init() })
+			let mut client = Client::from_raw_handle(target_handle.assume_init());
+			client.shared_event = self.shared_event.clone();
+			client.plugged_ids = self.plugged_ids.clone();
+			client.options = self.options;
+			Ok(client)
+		}
+	}
+}
+
+impl Client {
+	/// Wraps an already opened ViGEmBus device handle without reopening the device.
+	///
+	/// This allows a process which cannot open the device itself to use a handle inherited from or duplicated by another process.
+	///
+	/// # Safety
+	///
+	/// The handle must be a valid ViGEmBus device handle opened for overlapped I/O, as opened by [`Client::connect`].
+	/// Ownership of the handle transfers to the client, which closes it when dropped.
+	/// The handle must not be closed or wrapped by another client.
+	#[inline]
+	pub unsafe fn from_raw_handle(device: win_io::RawHandle) -> Client {
+		Client { device, free_slot: atomic::AtomicU32::new(1), shared_event: None, plugged_ids: None, options: ConnectOptions::default() }
+	}
+}
+
+/// Guidance for installing the ViGEmBus driver.
+///
+/// Carried by [`ConnectError::DriverNotInstalled`], applications can show it to the user or open the download page.
+#[cfg(feature = "install_hint")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct InstallHint {
+	/// Download page of the driver installer.
+	pub download_url: &'static str,
+	/// Instructions for the user.
+	pub guidance: &'static str,
+}
+
+#[cfg(feature = "install_hint")]
+impl InstallHint {
+	/// Installation guidance for the ViGEmBus driver.
+	pub const VIGEMBUS: InstallHint = InstallHint {
+		download_url: "https://github.com/nefarius/ViGEmBus/releases",
+		guidance: "Download and run the ViGEmBus setup from the releases page, then restart the application.",
+	};
+}
+
+/// Error returned by [`Client::connect_or_prompt`].
+#[cfg(feature = "install_hint")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConnectError {
+	/// The ViGEmBus driver is not installed, see [`Error::BusNotFound`].
+	DriverNotInstalled(InstallHint),
+	/// Connecting failed for another reason.
+	Other(Error),
+}
+
+#[cfg(feature = "install_hint")]
+impl fmt::Display for ConnectError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConnectError::DriverNotInstalled(hint) => write!(f, "the ViGEmBus driver is not installed, download it from {}", hint.download_url),
+			ConnectError::Other(err) => fmt::Display::fmt(err, f),
+		}
+	}
+}
+
+#[cfg(feature = "install_hint")]
+impl std::error::Error for ConnectError {}
+
+#[cfg(feature = "install_hint")]
+impl From<ConnectError> for Error {
+	#[inline]
+	fn from(err: ConnectError) -> Error {
+		match err {
+			ConnectError::DriverNotInstalled(_) => Error::BusNotFound,
+			ConnectError::Other(err) => err,
+		}
+	}
+}
+
+#[cfg(feature = "install_hint")]
+impl Client {
+	/// Connects to the ViGEmBus service, returning installation guidance if the driver is missing.
+	///
+	/// Like [`connect`](Self::connect) but [`Error::BusNotFound`] is returned as [`ConnectError::DriverNotInstalled`]
+	/// with the download page of the driver, so applications can turn the most common failure into an actionable message.
+	/// Nothing is installed automatically.
+	///
+	/// ```no_run
+	/// match vigem_client::Client::connect_or_prompt() {
+	///     Ok(client) => { /* ... */ },
+	///     Err(vigem_client::ConnectError::DriverNotInstalled(hint)) => {
+	///         eprintln!("{}\n{}", hint.guidance, hint.download_url);
+	///     },
+	///     Err(err) => eprintln!("{}", err),
+	/// }
+	/// ```
+	pub fn connect_or_prompt() -> Result<Client, ConnectError> {
+		match Client::connect() {
+			Ok(client) => Ok(client),
+			Err(Error::BusNotFound) => Err(ConnectError::DriverNotInstalled(InstallHint::VIGEMBUS)),
+			Err(err) => Err(ConnectError::Other(err)),
 		}
 	}
 }
 
+#[cfg(feature = "tokio")]
+impl Client {
+	// Waits for the target with the given serial number to become ready on the blocking thread pool.
+	pub(crate) async fn wait_ready_async(&self, serial_no: u32) -> Result<(), Error> {
+		// The blocking task owns a duplicated handle since it may outlive the caller's future
+		let client = self.try_clone()?;
+		let result = tokio::task::spawn_blocking(move || {
+			let event = Event::new(false, false);
+			let mut wait = bus::WaitDeviceReady::new(serial_no);
+			unsafe { wait.ioctl(client.device, event.handle) }
+		}).await;
+		match result {
+			Ok(result) => result.map_err(|err| Error::win(err, Operation::WaitReady)),
+			// The runtime is shutting down and dropped the task
+			Err(_) => Err(Error::OperationAborted),
+		}
+	}
+}
+
+// Serial numbers handed out by plugin scans are in the range 1..=SLOT_MAX.
+const SLOT_MAX: u32 = u16::MAX as u32;
+
+// Returns the serial number following `serial_no`, wrapping around to 1 after the last one.
+#[inline]
+fn next_slot(serial_no: u32) -> u32 {
+	match serial_no.checked_add(1) {
+		Some(next) if next <= SLOT_MAX => next,
+		_ => 1,
+	}
+}
+
+// Tries every serial number once with `try_plugin`, starting at `start` and wrapping around.
+fn scan(start: u32, deadline: Option<time::Instant>, mut try_plugin: impl FnMut(u32) -> bool) -> Result<u32, Error> {
+	let start = if (1..=SLOT_MAX).contains(&start) { start } else { 1 };
+
+	// Yes this is how the driver is implemented
+	let mut serial_no = start;
+	while !try_plugin(serial_no) {
+		serial_no = next_slot(serial_no);
+		if serial_no == start {
+			return Err(Error::NoFreeSlot);
+		}
+		if matches!(deadline, Some(deadline) if time::Instant::now() >= deadline) {
+			return Err(Error::Timeout);
+		}
+	}
+	Ok(serial_no)
+}
+
+impl Client {
+	// Searches for a free serial number with `try_plugin`, starting at `start` and wrapping around once.
+	// The free slot cache is updated with the slot following the one found.
+	pub(crate) fn scan_slots(&self, start: Option<u32>, deadline: Option<time::Instant>, try_plugin: impl FnMut(u32) -> bool) -> Result<u32, Error> {
+		let start = start.unwrap_or_else(|| self.free_slot.load(atomic::Ordering::Relaxed));
+		let serial_no = scan(start, deadline, try_plugin)?;
+		self.free_slot.store(next_slot(serial_no), atomic::Ordering::Relaxed);
+		Ok(serial_no)
+	}
+
+	// Plugs in a target with `plug`, rejecting it if a target with the same id is plugged in.
+	pub(crate) fn plug_unique(&self, id: TargetId, plug: impl FnOnce() -> Result<u32, Error>) -> Result<u32, Error> {
+		let result = self.plug_registered(id, plug);
+		match result {
+			Ok(serial_no) => log_debug!("plugged in target {:04x}:{:04x} with serial number {}", id.vendor, id.product, serial_no),
+			Err(err) => log_warn!("plugging in target {:04x}:{:04x} failed: {}", id.vendor, id.product, err),
+		}
+		result
+	}
+
+	#[inline]
+	fn plug_registered(&self, id: TargetId, plug: impl FnOnce() -> Result<u32, Error>) -> Result<u32, Error> {
+		let plugged_ids = match &self.plugged_ids {
+			Some(plugged_ids) => plugged_ids,
+			None => return plug(),
+		};
+		// Reserve the id so concurrent plugins of the same id cannot both succeed
+		if !plugged_ids.lock().unwrap_or_else(PoisonError::into_inner).insert(id) {
+			return Err(Error::DuplicateTargetId);
+		}
+		let result = plug();
+		if result.is_err() {
+			self.target_removed(id, None);
+		}
+		result
+	}
+
+	// Records that a target was unplugged so its id and serial number can be reused.
+	pub(crate) fn target_removed(&self, id: TargetId, serial_no: Option<u32>) {
+		if let Some(serial_no) = serial_no {
+			self.free_slot.fetch_min(serial_no, atomic::Ordering::Relaxed);
+		}
+		if let Some(plugged_ids) = &self.plugged_ids {
+			plugged_ids.lock().unwrap_or_else(PoisonError::into_inner).remove(&id);
+		}
+	}
+}
+
+// Parses a version string such as "1.21.442.0".
+fn parse_version(version: &str) -> Option<(u16, u16, u16, u16)> {
+	let mut parts = version.split('.').map(|part| part.trim().parse::<u16>());
+	let mut next = || parts.next().unwrap_or(Ok(0)).ok();
+	Some((next()?, next()?, next()?, next()?))
+}
+
 unsafe impl Sync for Client {}
 unsafe impl Send for Client {}
 
@@ -128,14 +778,20 @@ impl win_io::AsRawHandle for Client {
 }
 impl win_io::IntoRawHandle for Client {
 	#[inline]
-	fn into_raw_handle(self) -> HANDLE {
-		self.device
+	fn into_raw_handle(mut self) -> HANDLE {
+		// Ownership of the handle is transferred to the caller, do not close it
+		// but release the other fields which would be leaked by forgetting the client
+		drop(self.shared_event.take());
+		drop(self.plugged_ids.take());
+		let device = self.device;
+		mem::forget(self);
+		device
 	}
 }
 impl win_io::FromRawHandle for Client {
 	#[inline]
 	unsafe fn from_raw_handle(device: HANDLE) -> Client {
-		Client { device }
+		Client::from_raw_handle(device)
 	}
 }
 
@@ -147,3 +803,26 @@ impl Drop for Client {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn scan_tries_every_serial() {
+		for &start in &[1, 2, SLOT_MAX - 1, SLOT_MAX, 0, SLOT_MAX + 1] {
+			let mut tried = HashSet::new();
+			assert_eq!(scan(start, None, |serial_no| { assert!(tried.insert(serial_no)); false }), Err(Error::NoFreeSlot));
+			assert_eq!(tried.len(), SLOT_MAX as usize);
+			assert!(tried.contains(&1) && tried.contains(&SLOT_MAX));
+		}
+	}
+
+	#[test]
+	fn scan_finds_last_serial() {
+		assert_eq!(scan(SLOT_MAX - 1, None, |serial_no| serial_no == SLOT_MAX), Ok(SLOT_MAX));
+		assert_eq!(scan(3, None, |serial_no| serial_no == SLOT_MAX), Ok(SLOT_MAX));
+		assert_eq!(next_slot(SLOT_MAX), 1);
+		assert_eq!(next_slot(u32::MAX), 1);
+	}
+}
@@ -1,57 +1,295 @@
 use crate::*;
 use std::borrow::Borrow;
-use std::{marker, pin, thread};
-use std::{fmt, mem, ptr};
+use std::{marker, pin, thread, time};
+use std::{fmt, io, mem, ops, ptr};
+use std::collections::VecDeque;
+use std::sync::{atomic, Arc, Mutex, PoisonError};
+use std::os::windows::io::RawHandle;
 
 mod button;
 mod reports;
+mod sequence;
+#[cfg(feature = "gilrs")]
+mod gilrs;
+#[cfg(feature = "hidapi")]
+mod hidapi;
 
 use winapi::shared::winerror;
+use winapi::shared::ntdef::HANDLE;
+use winapi::um::winnt::MAXIMUM_WAIT_OBJECTS;
+use winapi::um::winbase::{WAIT_FAILED, WAIT_OBJECT_0};
+use winapi::um::synchapi::WaitForMultipleObjects;
+use winapi::um::errhandlingapi::GetLastError;
 
 pub use button::*;
 pub use reports::*;
+pub use sequence::*;
+#[cfg(feature = "hidapi")]
+pub use self::hidapi::mirror_physical_ds4;
 
 pub struct DSRequestNotification {
-	client: Client,
+	client: Arc<Client>,
 	ds4rn: bus::RequestNotification,
+	last_report: Option<DS4OutputReport>,
 	_unpin: marker::PhantomPinned,
 }
 
 
 impl DSRequestNotification {
+	#[inline]
+	fn new(client: Arc<Client>, serial_no: u32) -> DSRequestNotification {
+		let ds4rn = bus::RequestNotification::new(bus::RequestNotificationVariant::DS4(bus::DS4RequestNotification::new(serial_no)));
+		DSRequestNotification { client, ds4rn, last_report: None, _unpin: marker::PhantomPinned }
+	}
+
 	/// Returns if the underlying target is still attached.
 	#[inline]
 	pub fn is_attached(&self) -> bool {
-		match self.ds4rn.buffer {
-			bus::RequestNotificationVariant::DS4(ref buffer) => buffer.SerialNo != 0,
-			#[allow(unreachable_patterns)]
-			_ => unreachable!()
+		self.ds4rn.serial_no() != 0
+	}
+
+	/// Returns the event handle which is signaled when a requested notification completes.
+	///
+	/// The handle can be included in a custom `WaitForMultipleObjects` loop, after which the
+	/// notification is retrieved with [`poll(false)`](Self::poll).
+	/// The event is auto-reset, waiting on it consumes the signal and a subsequent `poll(true)` would block.
+	///
+	/// The handle is owned by this object and is only valid for as long as it is alive.
+	/// The caller must not close, signal or reset the handle.
+	#[inline]
+	pub fn event_handle(&self) -> RawHandle {
+		self.ds4rn.overlapped.hEvent
+	}
+
+	/// Returns the last notification delivered to the callback of [`spawn_thread`](Self::spawn_thread)
+	/// or [`spawn_coalescing_thread`](Self::spawn_coalescing_thread).
+	///
+	/// While the callback runs this is the notification delivered before the current one,
+	/// which allows detecting changes such as the rumble starting or stopping.
+	/// Notifications retrieved with [`poll`](Self::poll) are not recorded.
+	#[inline]
+	pub fn last_report(&self) -> Option<DS4OutputReport> {
+		self.last_report
+	}
+
+	/// Waits for the next output report written by the game, preserving the raw bytes.
+	///
+	/// Blocks the current thread until the game writes an output report, see [`DS4OutputReportEx`].
+	/// These reports are queued by the driver separately from the notifications received with [`poll`](Self::poll).
+	///
+	/// Returns [`Error::Unsupported`] if the driver is older than version 1.17
+	/// and [`Error::OperationAborted`] if the target is unplugged while waiting.
+	#[inline(never)]
+	pub fn await_output(&self) -> Result<DS4OutputReportEx, Error> {
+		let serial_no = self.ds4rn.serial_no();
+		if serial_no == 0 {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let event = Event::new(false, false);
+		let mut await_output = bus::DS4AwaitOutput::new(serial_no);
+		match unsafe { await_output.ioctl(self.client.device, event.handle) } {
+			Ok(()) => Ok(DS4OutputReportEx::from_raw(await_output.Report)),
+			Err(winerror::ERROR_INVALID_PARAMETER) | Err(winerror::ERROR_INVALID_FUNCTION) => Err(Error::Unsupported),
+			Err(winerror::ERROR_OPERATION_ABORTED) => Err(Error::OperationAborted),
+			Err(err) => Err(Error::win(err, Operation::Notify)),
 		}
 	}
 
+	// Invokes the callback and records the notification as the last delivered one.
+	#[inline]
+	fn deliver<F: FnMut(&DSRequestNotification, DS4OutputReport)>(mut self: pin::Pin<&mut Self>, data: DS4OutputReport, f: &mut F) {
+		f(&self, data);
+		// Safety: the last report is not structurally pinned
+		unsafe { self.as_mut().get_unchecked_mut().last_report = Some(data) };
+	}
+
 	/// Spawns a thread to handle the notifications.
 	///
 	/// The callback `f` is invoked for every notification.
 	///
-	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread.
-	/// It is recommended to join the thread after the target from which the notifications are requested is dropped.
+	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread and a [`NotificationStop`] handle to stop it.
+	/// It is recommended to join the thread after the target from which the notifications are requested is dropped,
+	/// or after [`NotificationStop::stop`] is called.
+	///
+	/// The thread returns `Ok(())` when it ends normally, because the target was unplugged or dropped or the thread was stopped.
+	/// It returns the error if polling failed for any other reason, which may warrant recreating the target.
+	///
+	/// A panic in the callback ends the thread, joining the thread returns the panic as an error.
+	/// Use [`spawn_thread_with_options`](Self::spawn_thread_with_options) to keep the thread running instead.
+	#[inline]
+	pub fn spawn_thread<F: FnMut(&DSRequestNotification, DS4OutputReport) + Send + 'static>(self, f: F) -> (thread::JoinHandle<Result<(), Error>>, NotificationStop) {
+		self.spawn_thread_with_options(SpawnOptions::new(), f)
+	}
+
+	/// Spawns a thread to handle the notifications with the given options.
+	///
+	/// See [`spawn_thread`](Self::spawn_thread) and [`SpawnOptions`].
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	///
+	/// let options = vigem_client::SpawnOptions::new().name("rumble").catch_panics(true);
+	/// let (_thread, _stop) = target.request_notification().unwrap().spawn_thread_with_options(options, |_, report| {
+	///     println!("{:?}", report);
+	/// });
+	/// ```
 	#[inline]
-	pub fn spawn_thread<F: FnMut(&DSRequestNotification, bus::DS4OutputReport) + Send + 'static>(self, mut f: F) -> thread::JoinHandle<()> {
-		thread::spawn(move || {
+	pub fn spawn_thread_with_options<F: FnMut(&DSRequestNotification, DS4OutputReport) + Send + 'static>(self, options: SpawnOptions, mut f: F) -> (thread::JoinHandle<Result<(), Error>>, NotificationStop) {
+		let stop = NotificationStop::new();
+		let state = stop.state.clone();
+		let thread = options.clone().spawn(move || {
 			// Safety: the request notification object is not accessible after it is pinned
 			let mut reqn = self;
 			let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
+			// The pending request may be cancelled from another thread while the object is pinned here
+			let device = reqn.client.device;
+			let overlapped = unsafe { &mut reqn.as_mut().get_unchecked_mut().ds4rn.overlapped as *mut _ };
+			let _registration = state.register(device, overlapped);
 			loop {
-				reqn.as_mut().request();
+				{
+					// Checking the flag and issuing the request must not race with `stop`
+					let _pending = state.lock();
+					if state.stopped.load(atomic::Ordering::SeqCst) {
+						break Ok(());
+					}
+					reqn.as_mut().request();
+				}
 				let result = reqn.as_mut().poll(true);
 				match result {
 					Ok(None) => {},
-					Ok(Some(data)) => f(&reqn, data),
-					// When the target is dropped the notification request is aborted
-					Err(_) => break,
+					Ok(Some(data)) => options.invoke(|| reqn.as_mut().deliver(data, &mut f)),
+					// When the target is dropped or the thread is stopped the notification request is aborted
+					Err(Error::OperationAborted) => break Ok(()),
+					Err(err) => break Err(err),
+				}
+			}
+		});
+		(thread, stop)
+	}
+
+	/// Handles the notifications on the current thread until the target is unplugged or dropped.
+	///
+	/// Runs the same loop as [`spawn_thread`](Self::spawn_thread) without creating a thread,
+	/// for callers which already have a dedicated thread or loop. The callback `f` is invoked for every notification.
+	///
+	/// Returns `Ok(())` when the target was unplugged or dropped and the error if polling failed for any other reason.
+	/// A panic in the callback unwinds out of this method.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	///
+	/// let notification = target.request_notification().unwrap();
+	/// std::thread::spawn(move || {
+	///     notification.run_blocking(|_, report| println!("{:?}", report))
+	/// });
+	/// ```
+	#[inline(never)]
+	pub fn run_blocking<F: FnMut(&DSRequestNotification, DS4OutputReport)>(self, mut f: F) -> Result<(), Error> {
+		// Safety: the request notification object is not accessible after it is pinned
+		let mut reqn = self;
+		let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
+		loop {
+			reqn.as_mut().request();
+			match reqn.as_mut().poll(true) {
+				Ok(None) => {},
+				Ok(Some(data)) => reqn.as_mut().deliver(data, &mut f),
+				// The request is aborted when the target is unplugged or dropped
+				Err(Error::OperationAborted) => return Ok(()),
+				Err(err) => return Err(err),
+			}
+		}
+	}
+
+	/// Spawns a thread to handle the notifications, invoking the callback at most once per `interval`.
+	///
+	/// Notifications received within the interval are coalesced and the callback `f` is invoked with the most recent one,
+	/// intermediate notifications are discarded.
+	/// A notification still held back when the target is unplugged or the thread is stopped is delivered before the thread exits.
+	///
+	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread and a [`NotificationStop`] handle to stop it,
+	/// see [`spawn_thread`](Self::spawn_thread).
+	#[inline]
+	pub fn spawn_coalescing_thread<F: FnMut(&DSRequestNotification, DS4OutputReport) + Send + 'static>(self, interval: time::Duration, mut f: F) -> (thread::JoinHandle<Result<(), Error>>, NotificationStop) {
+		let stop = NotificationStop::new();
+		let state = stop.state.clone();
+		let thread = thread::spawn(move || {
+			// Safety: the request notification object is not accessible after it is pinned
+			let mut reqn = self;
+			let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
+			// The pending request may be cancelled from another thread while the object is pinned here
+			let device = reqn.client.device;
+			let overlapped = unsafe { &mut reqn.as_mut().get_unchecked_mut().ds4rn.overlapped as *mut _ };
+			let _registration = state.register(device, overlapped);
+			let mut latest: Option<DS4OutputReport> = None;
+			let mut last_call: Option<time::Instant> = None;
+			let result = 'requests: loop {
+				{
+					// Checking the flag and issuing the request must not race with `stop`
+					let _pending = state.lock();
+					if state.stopped.load(atomic::Ordering::SeqCst) {
+						break Ok(());
+					}
+					reqn.as_mut().request();
+				}
+				loop {
+					// Only wait for the next notification as long as the held back one may be delayed
+					let result = match latest {
+						Some(_) => {
+							let elapsed = last_call.map_or(interval, |last_call| last_call.elapsed());
+							reqn.as_mut().poll_timeout(interval.saturating_sub(elapsed))
+						},
+						None => reqn.as_mut().poll(true),
+					};
+					match result {
+						Ok(None) => {
+							// The interval elapsed while the request is still pending
+							if let Some(data) = latest.take() {
+								last_call = Some(time::Instant::now());
+								reqn.as_mut().deliver(data, &mut f);
+							}
+						},
+						Ok(Some(data)) => {
+							if last_call.is_none_or(|last_call| last_call.elapsed() >= interval) {
+								latest = None;
+								last_call = Some(time::Instant::now());
+								reqn.as_mut().deliver(data, &mut f);
+							}
+							else {
+								latest = Some(data);
+							}
+							continue 'requests;
+						},
+						// When the target is dropped or the thread is stopped the notification request is aborted
+						Err(Error::OperationAborted) => break 'requests Ok(()),
+						Err(err) => break 'requests Err(err),
+					}
 				}
+			};
+			if let Some(data) = latest {
+				reqn.as_mut().deliver(data, &mut f);
 			}
-		})
+			result
+		});
+		(thread, stop)
+	}
+
+	/// Turns the notifications into an async stream.
+	///
+	/// The notifications are received on a separate thread, see [`spawn_thread`](Self::spawn_thread).
+	/// The stream ends when the underlying target is unplugged, dropping the stream stops the thread.
+	#[cfg(feature = "tokio")]
+	#[inline]
+	pub fn into_stream(self) -> DSNotificationStream {
+		let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+		let (_thread, stop) = self.spawn_thread(move |_, report| {
+			let _ = sender.send(report);
+		});
+		DSNotificationStream { receiver, stop }
 	}
 
 	/// Requests a notification.
@@ -60,14 +298,8 @@ impl DSRequestNotification {
 		unsafe {
 			let device = self.client.device;
 			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
-			match ds4rn.buffer {
-				bus::RequestNotificationVariant::DS4(ref mut buffer) => {
-					if buffer.SerialNo != 0 {
-						ds4rn.ioctl(device);
-					}
-				},
-				#[allow(unreachable_patterns)]
-				_ => unreachable!()
+			if ds4rn.serial_no() != 0 {
+				ds4rn.ioctl(device);
 			}
 		}
 	}
@@ -79,46 +311,104 @@ impl DSRequestNotification {
 	///
 	/// Returns:
 	///
-	/// * `Ok(None)`: When `wait` is false and there is no notification yet.
+	/// * `Ok(None)`: When `wait` is false and there is no notification yet, the request is still pending.
 	/// * `Ok(Some(_))`: The notification was successfully received.  
 	///   Another request should be made or any other calls to `poll` return the same result.
 	/// * `Err(OperationAborted)`: The underlying target was unplugged causing any pending notification requests to abort.
-	/// * `Err(_)`: An unexpected error occurred.
+	/// * `Err(_)`: An unexpected error occurred, including the last [`request`](Self::request) failing to start.
+	#[inline(never)]
+	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<DS4OutputReport>, Error> {
+		self.poll_with(|ds4rn, device| unsafe { ds4rn.poll(device, wait) })
+	}
+
+	/// Discards the notifications which are already available, returning how many were discarded.
+	///
+	/// Repeatedly requests a notification and polls it without blocking until none is available,
+	/// the last request is cancelled so no request is left pending.
+	/// Use it before re-entering a notification loop after a pause, not while a request is pending.
+	///
+	/// Returns [`Error::OperationAborted`] as soon as a request is aborted because the underlying target was unplugged.
 	#[inline(never)]
-	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<bus::DS4OutputReport>, Error> {
+	pub fn drain(mut self: pin::Pin<&mut Self>) -> Result<usize, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let mut count = 0;
+		loop {
+			self.as_mut().request();
+			match self.as_mut().poll(false)? {
+				Some(_) => count += 1,
+				None => break,
+			}
+		}
+
 		unsafe {
 			let device = self.client.device;
 			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
-			match ds4rn.poll(device, wait) {
-				Ok(()) => {
-					match &ds4rn.buffer {
-						bus::RequestNotificationVariant::DS4(buffer) => {
-							Ok(Some(bus::DS4OutputReport {
-								small_motor: buffer.Report.small_motor,
-								large_motor: buffer.Report.large_motor,
-								lightbar_color: buffer.Report.lightbar_color,
-							}))
-						},
-						#[allow(unreachable_patterns)]
-						_ => unreachable!()
-					}
-				},
-				Err(winerror::ERROR_IO_INCOMPLETE) => Ok(None),
-				Err(winerror::ERROR_OPERATION_ABORTED) => {
-					// Operation was aborted, fail all future calls
-					// The is aborted when the underlying target is unplugged
-					// This has the potential for a race condition:
-					//  What happens if a new target is plugged inbetween calls to poll and request...
-					match ds4rn.buffer {
-						bus::RequestNotificationVariant::DS4(ref mut buffer) => { buffer.SerialNo = 0; },
-						#[allow(unreachable_patterns)]
-						_ => unreachable!()
-					}
-					Err(Error::OperationAborted)
-				},
-				Err(err) => Err(Error::WinError(err)),
+			// The last request may have completed before it was cancelled
+			if ds4rn.cancel(device).map_err(|err| Error::win(err, Operation::Notify))? {
+				count += 1;
 			}
 		}
+		Ok(count)
+	}
+
+	/// Polls the request for notifications, waiting at most `timeout` for a notification to arrive.
+	///
+	/// The timeout is rounded up to whole milliseconds, a non-zero sub-millisecond timeout waits for at least one millisecond.
+	/// Timeouts larger than `u32::MAX` milliseconds wait indefinitely.
+	///
+	/// Returns the same results as [`poll`](Self::poll), where `Ok(None)` means the timeout elapsed without a notification.
+	#[inline(never)]
+	pub fn poll_timeout(self: pin::Pin<&mut Self>, timeout: time::Duration) -> Result<Option<DS4OutputReport>, Error> {
+		let timeout_ms = event::timeout_ms(timeout);
+		self.poll_with(|ds4rn, device| unsafe { ds4rn.poll_timeout(device, timeout_ms) })
+	}
+
+	#[inline]
+	fn poll_with<F: FnOnce(&mut bus::RequestNotification, HANDLE) -> Result<(), u32>>(self: pin::Pin<&mut Self>, f: F) -> Result<Option<DS4OutputReport>, Error> {
+		unsafe {
+			let device = self.client.device;
+			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
+			let result = f(ds4rn, device);
+			poll_result(ds4rn, result)
+		}
+	}
+}
+
+// Translates the result of polling a DS4 notification request.
+#[inline]
+fn poll_result(ds4rn: &mut bus::RequestNotification, result: Result<(), u32>) -> Result<Option<DS4OutputReport>, Error> {
+	match result {
+		Ok(()) => {
+			match &ds4rn.buffer {
+				bus::RequestNotificationVariant::DS4(buffer) => {
+					Ok(Some(DS4OutputReport {
+						small_motor: buffer.Report.small_motor,
+						large_motor: buffer.Report.large_motor,
+						lightbar_color: buffer.Report.lightbar_color,
+					}))
+				},
+				bus::RequestNotificationVariant::X360(_) => Err(Error::InternalVariantMismatch),
+			}
+		},
+		// The request is still pending
+		Err(winerror::ERROR_IO_INCOMPLETE) | Err(winerror::ERROR_IO_PENDING) => Ok(None),
+		Err(winerror::ERROR_OPERATION_ABORTED) => {
+			// Operation was aborted, fail all future calls
+			// The is aborted when the underlying target is unplugged
+			// This has the potential for a race condition:
+			//  What happens if a new target is plugged inbetween calls to poll and request...
+			log_debug!("notification request for serial number {} aborted", ds4rn.serial_no());
+			ds4rn.clear_serial_no();
+			Err(Error::OperationAborted)
+		},
+		Err(err) => {
+			let err = Error::win(err, Operation::Notify);
+			log_warn!("notification request for serial number {} failed: {}", ds4rn.serial_no(), err);
+			Err(err)
+		},
 	}
 }
 unsafe impl Sync for DSRequestNotification {}
@@ -126,14 +416,9 @@ unsafe impl Send for DSRequestNotification {}
 
 impl fmt::Debug for DSRequestNotification {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		let buffer = match &self.ds4rn.buffer {
-			bus::RequestNotificationVariant::DS4(buffer) => buffer,
-			#[allow(unreachable_patterns)]
-			_ => unreachable!()
-		};
 		f.debug_struct("DSRequestNotification")
 			.field("client", &format_args!("{:?}", self.client))
-			.field("serial_no", &buffer.SerialNo)
+			.field("serial_no", &self.ds4rn.serial_no())
 			.finish()
 	}
 }
@@ -143,12 +428,95 @@ impl Drop for DSRequestNotification {
 	fn drop(&mut self) {
 		unsafe {
 			let this = pin::Pin::new_unchecked(self);
-			let serial_no = match &this.ds4rn.buffer {
-				bus::RequestNotificationVariant::DS4(buffer) => buffer.SerialNo,
-				#[allow(unreachable_patterns)]
-				_ => unreachable!()
-			};
-			if serial_no != 0 {
+			if this.ds4rn.serial_no() != 0 {
+				let device = this.client.device;
+				let ds4rn = &mut this.get_unchecked_mut().ds4rn;
+				let _ = ds4rn.cancel(device);
+			}
+		}
+	}
+}
+
+
+/// DualShock4 notification request borrowing the client of its target.
+///
+/// Created with [`DualShock4Wired::request_notification_borrowed`], it does not duplicate the client handle
+/// but cannot outlive the target and cannot be moved to another thread with `spawn_thread`.
+/// Poll it inline after pinning it, eg. with `Box::pin`.
+pub struct DSRequestNotificationRef<'a> {
+	client: &'a Client,
+	ds4rn: bus::RequestNotification,
+	_unpin: marker::PhantomPinned,
+}
+
+impl<'a> DSRequestNotificationRef<'a> {
+	/// Returns if the underlying target is still attached.
+	#[inline]
+	pub fn is_attached(&self) -> bool {
+		self.ds4rn.serial_no() != 0
+	}
+
+	/// Returns the event handle which is signaled when a requested notification completes.
+	///
+	/// See [`DSRequestNotification::event_handle`].
+	#[inline]
+	pub fn event_handle(&self) -> RawHandle {
+		self.ds4rn.overlapped.hEvent
+	}
+
+	/// Requests a notification.
+	#[inline(never)]
+	pub fn request(self: pin::Pin<&mut Self>) {
+		unsafe {
+			let device = self.client.device;
+			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
+			if ds4rn.serial_no() != 0 {
+				ds4rn.ioctl(device);
+			}
+		}
+	}
+
+	/// Polls the request for notifications.
+	///
+	/// Returns the same results as [`DSRequestNotification::poll`].
+	#[inline(never)]
+	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<DS4OutputReport>, Error> {
+		unsafe {
+			let device = self.client.device;
+			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
+			let result = ds4rn.poll(device, wait);
+			poll_result(ds4rn, result)
+		}
+	}
+
+	/// Polls the request for notifications, waiting at most `timeout` for a notification to arrive.
+	///
+	/// Returns the same results as [`DSRequestNotification::poll_timeout`].
+	#[inline(never)]
+	pub fn poll_timeout(self: pin::Pin<&mut Self>, timeout: time::Duration) -> Result<Option<DS4OutputReport>, Error> {
+		unsafe {
+			let device = self.client.device;
+			let ds4rn = &mut self.get_unchecked_mut().ds4rn;
+			let result = ds4rn.poll_timeout(device, event::timeout_ms(timeout));
+			poll_result(ds4rn, result)
+		}
+	}
+}
+
+impl<'a> fmt::Debug for DSRequestNotificationRef<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DSRequestNotificationRef")
+			.field("client", &format_args!("{:?}", self.client))
+			.field("serial_no", &self.ds4rn.serial_no())
+			.finish()
+	}
+}
+
+impl<'a> Drop for DSRequestNotificationRef<'a> {
+	fn drop(&mut self) {
+		unsafe {
+			let this = pin::Pin::new_unchecked(self);
+			if this.ds4rn.serial_no() != 0 {
 				let device = this.client.device;
 				let ds4rn = &mut this.get_unchecked_mut().ds4rn;
 				let _ = ds4rn.cancel(device);
@@ -157,25 +525,265 @@ impl Drop for DSRequestNotification {
 	}
 }
 
+/// A set of DualShock4 notification requests polled with a single wait.
+///
+/// Handles the notifications of many targets from one thread instead of a thread per target.
+/// At most `MAXIMUM_WAIT_OBJECTS` (64) notifications can be added to a set.
+///
+/// ```no_run
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut first = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+/// let mut second = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+/// first.plugin().unwrap();
+/// second.plugin().unwrap();
+///
+/// let mut set = vigem_client::NotificationSet::new();
+/// set.push(first.request_notification().unwrap()).unwrap();
+/// set.push(second.request_notification().unwrap()).unwrap();
+///
+/// while let Ok((index, report)) = set.poll_any(std::time::Duration::from_secs(1)) {
+///     println!("target {}: {:?}", index, report);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct NotificationSet {
+	notifications: Vec<pin::Pin<Box<DSRequestNotification>>>,
+	// Whether a request is outstanding for the notification at the same index
+	pending: Vec<bool>,
+	// Index the next wait starts at so a busy notification cannot starve the others
+	next: usize,
+}
+
+impl NotificationSet {
+	/// Creates an empty set.
+	#[inline]
+	pub fn new() -> NotificationSet {
+		NotificationSet::default()
+	}
+
+	/// Adds a notification request, returning its index in the set.
+	///
+	/// Returns the notification back if the set already holds `MAXIMUM_WAIT_OBJECTS` notifications.
+	#[inline]
+	pub fn push(&mut self, notification: DSRequestNotification) -> Result<usize, DSRequestNotification> {
+		if self.notifications.len() >= MAXIMUM_WAIT_OBJECTS as usize {
+			return Err(notification);
+		}
+		self.notifications.push(Box::pin(notification));
+		self.pending.push(false);
+		Ok(self.notifications.len() - 1)
+	}
+
+	/// Returns the number of notifications in the set, including those of unplugged targets.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.notifications.len()
+	}
+
+	/// Returns if the set holds no notifications.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.notifications.is_empty()
+	}
+
+	/// Returns the notification at `index`.
+	#[inline]
+	pub fn get(&self, index: usize) -> Option<&DSRequestNotification> {
+		self.notifications.get(index).map(|notification| &**notification)
+	}
+
+	/// Waits at most `timeout` for a notification from any target in the set.
+	///
+	/// Returns the index of the notification, as returned by [`push`](Self::push), together with the report.
+	/// Requests are issued as needed and stay outstanding between calls so no report is missed.
+	///
+	/// A target which is unplugged is skipped from then on, its index stays valid and
+	/// [`is_attached`](DSRequestNotification::is_attached) returns false for it.
+	/// Returns [`Error::NotPluggedIn`] if no target in the set is attached and [`Error::Timeout`] if no notification arrived in time.
+	/// A request which fails to start returns its error right away, the request is issued again by the next call.
+	#[inline(never)]
+	pub fn poll_any(&mut self, timeout: time::Duration) -> Result<(usize, DS4OutputReport), Error> {
+		let deadline = time::Instant::now().checked_add(timeout);
+		loop {
+			let count = self.notifications.len();
+			let mut indices = [0usize; MAXIMUM_WAIT_OBJECTS as usize];
+			let mut handles = [ptr::null_mut(); MAXIMUM_WAIT_OBJECTS as usize];
+			let mut len = 0;
+			for offset in 0..count {
+				let index = (self.next + offset) % count;
+				let notification = &mut self.notifications[index];
+				if !notification.is_attached() {
+					continue;
+				}
+				if !self.pending[index] {
+					notification.as_mut().request();
+					// A request which failed to start never signals its event, report the error instead of timing out
+					match notification.as_mut().poll(false) {
+						Ok(_) => self.pending[index] = true,
+						Err(Error::OperationAborted) => continue,
+						Err(err) => return Err(err),
+					}
+				}
+				indices[len] = index;
+				handles[len] = notification.event_handle();
+				len += 1;
+			}
+			if len == 0 {
+				return Err(Error::NotPluggedIn);
+			}
+
+			let remaining = match deadline {
+				Some(deadline) => deadline.saturating_duration_since(time::Instant::now()),
+				None => time::Duration::MAX,
+			};
+			let wait = unsafe { WaitForMultipleObjects(len as u32, handles.as_ptr(), /*bWaitAll: */0, event::timeout_ms(remaining)) };
+			let index = match wait {
+				winerror::WAIT_TIMEOUT => return Err(Error::Timeout),
+				WAIT_FAILED => return Err(Error::win(unsafe { GetLastError() }, Operation::Notify)),
+				wait if wait.wrapping_sub(WAIT_OBJECT_0) < len as u32 => indices[(wait - WAIT_OBJECT_0) as usize],
+				// `WAIT_ABANDONED_*` is only returned for mutexes, the handles are not notification events
+				_ => return Err(Error::win(winerror::ERROR_INVALID_HANDLE, Operation::Notify)),
+			};
+
+			// The event was consumed by the wait, the completed request is retrieved without waiting
+			self.pending[index] = false;
+			self.next = index + 1;
+			match self.notifications[index].as_mut().poll(false) {
+				Ok(Some(report)) => return Ok((index, report)),
+				// The request is still pending, eg. the event was signaled by a request which completed immediately
+				Ok(None) => self.pending[index] = true,
+				// The target was unplugged, keep waiting for the others
+				Err(Error::OperationAborted) => (),
+				Err(err) => return Err(err),
+			}
+		}
+	}
+}
+
+/// Async stream of DualShock4 notifications.
+///
+/// Created by [`DSRequestNotification::into_stream`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct DSNotificationStream {
+	receiver: tokio::sync::mpsc::UnboundedReceiver<DS4OutputReport>,
+	stop: NotificationStop,
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for DSNotificationStream {
+	type Item = DS4OutputReport;
+
+	#[inline]
+	fn poll_next(self: pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<DS4OutputReport>> {
+		self.get_mut().receiver.poll_recv(cx)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for DSNotificationStream {
+	fn drop(&mut self) {
+		self.stop.stop();
+	}
+}
 
 /// A virtual Sony DualShock 4 (wired).
+///
+/// ViGEmBus only emulates the wired USB DualShock 4, there is no Bluetooth target type.
+/// The battery status reported in [`DS4Status`] uses the wired encoding with the cable connected.
+///
+/// # Detecting consumers
+///
+/// ViGEmBus does not report when an application opens or closes the virtual controller,
+/// neither through the notification channel nor through a separate ioctl.
+/// Opening the device is handled by the HID stack above the bus driver, which never sees it,
+/// so there is no way to wait for a consumer to attach.
+///
+/// Output reports are the only signal the driver forwards.
+/// Many games write one when they start using the controller, eg. to set the lightbar color,
+/// which makes the first notification from [`request_notification`](Self::request_notification) or [`read_output`](Self::read_output)
+/// a usable heuristic. It is not a guarantee: applications which only read input never send output reports,
+/// and nothing is sent when an application stops reading.
+///
+/// # Firmware and hardware version
+///
+/// The firmware and hardware versions cannot be customized.
+/// ViGEmBus answers the HID feature report requests itself, including the version report,
+/// with fixed data captured from a real controller, and no ioctl or report field exposes them to clients.
+/// The vendor and product ids given to [`new`](Self::new) are the only identity a target can change.
 pub struct DualShock4Wired<CL: Borrow<Client>> {
 	client: CL,
-	event: Event,
+	event: TargetEvent,
 	serial_no: u32,
 	id: TargetId,
+	ready: bool,
+	last_report: Option<DS4Report>,
+	limiter: Option<RateLimiter>,
+	strict: bool,
+	metrics: Option<TargetMetrics>,
+	packet_counter: Option<u8>,
+	// Submit request reused by every update, holds the last submitted report
+	buffer: bus::DS4SubmitReport,
+	// Updates submitted with `update_async`, the mutex only keeps the controller `Sync`
+	updates: Mutex<AsyncUpdates>,
+}
+
+#[derive(Default)]
+struct AsyncUpdates {
+	// Pending updates in submission order
+	pending: VecDeque<PendingUpdate>,
+	// Completed requests kept for reuse, boxed as they move to and from `pending` which needs stable addresses
+	#[allow(clippy::vec_box)]
+	spare: Vec<Box<bus::DS4SubmitReportAsync>>,
+}
+
+struct PendingUpdate {
+	request: Box<bus::DS4SubmitReportAsync>,
+	callback: Box<dyn FnOnce(Result<(), Error>) + Send>,
 }
 
 impl<CL: Borrow<Client>> DualShock4Wired<CL> {
+	/// Interval between the reports of a real wired controller, which reports at about 250Hz.
+	pub const REPORT_INTERVAL: time::Duration = time::Duration::from_millis(4);
+
 	/// Creates a new instance.
+	///
+	/// The vendor and product ids are presented to applications as is when the controller is plugged in,
+	/// use eg. [`TargetId::DUALSHOCK4_V2`] for games which expect the second hardware revision.
+	/// Only the ids change, the reports are the same for every revision.
 	#[inline]
 	pub fn new(client: CL, id: TargetId) -> DualShock4Wired<CL> {
-		let event = Event::new(false, false);
+		DualShock4Wired::with_event_mode(client, id, EventMode::AutoReset)
+	}
+
+	/// Creates a new instance waiting on an event with the given reset mode.
+	///
+	/// [`new`](Self::new) uses [`EventMode::AutoReset`], see [`EventMode`] for when the manual-reset mode may help.
+	/// The mode is ignored when the client is in [shared event mode](Client::shared_event_mode),
+	/// the targets then wait on the client's auto-reset event.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let id = vigem_client::TargetId::DUALSHOCK4_WIRED;
+	/// let mut target = vigem_client::DualShock4Wired::with_event_mode(&client, id, vigem_client::EventMode::ManualReset);
+	/// target.plugin().unwrap();
+	/// ```
+	#[inline]
+	pub fn with_event_mode(client: CL, id: TargetId, mode: EventMode) -> DualShock4Wired<CL> {
+		let event = TargetEvent::new(client.borrow().shared_event.as_ref(), mode);
 		DualShock4Wired {
 			client,
 			event,
 			serial_no: 0,
 			id,
+			ready: false,
+			last_report: None,
+			limiter: None,
+			strict: false,
+			metrics: None,
+			packet_counter: None,
+			buffer: bus::DS4SubmitReport::new(0, DS4Report::NEUTRAL),
+			updates: Mutex::default(),
 		}
 	}
 
@@ -185,12 +793,53 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 		self.serial_no != 0
 	}
 
+	/// Returns if the controller is plugged in and [`wait_ready`](Self::wait_ready) has completed.
+	///
+	/// Updates submitted while the controller is attached but not yet ready may return an error.
+	/// The controller is no longer ready after it is unplugged.
+	#[inline]
+	pub fn is_ready(&self) -> bool {
+		self.is_attached() && self.ready
+	}
+
+	/// Returns the serial number assigned by the driver when plugged in.
+	///
+	/// The serial number identifies the target on the bus for as long as it is plugged in.
+	/// The driver does not expose the Windows device instance path of the target,
+	/// only the serial number it was plugged in with.
+	#[inline]
+	pub fn serial_no(&self) -> Option<u32> {
+		if self.is_attached() { Some(self.serial_no) } else { None }
+	}
+
+	/// Returns a snapshot of the id and serial number of the controller.
+	///
+	/// The snapshot does not follow later changes, eg. when the controller is unplugged.
+	#[inline]
+	pub fn snapshot(&self) -> TargetHandle {
+		TargetHandle { id: self.id, serial_no: self.serial_no() }
+	}
+
 	/// Returns the id the controller was constructed with.
 	#[inline]
 	pub fn id(&self) -> TargetId {
 		self.id
 	}
 
+	/// Returns the event handle used for the requests made by this controller.
+	///
+	/// The event is auto-reset and is signaled whenever a request issued by this controller completes.
+	/// It can be included in a custom `WaitForMultipleObjects` loop, but must not be waited on
+	/// while a method of this controller is running as that would consume the signal the method is waiting for.
+	///
+	/// The handle is owned by the controller and is only valid for as long as the controller is alive.
+	/// The caller must not close, signal or reset the handle.
+	/// When the client is in [shared event mode](Client::shared_event_mode) the event is shared with all the targets of the client.
+	#[inline]
+	pub fn event_handle(&self) -> RawHandle {
+		self.event.handle()
+	}
+
 	/// Returns the client.
 	#[inline]
 	pub fn client(&self) -> &CL {
@@ -201,57 +850,188 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 	#[inline]
 	pub fn drop(mut self) -> CL {
 		let _ = self.unplug();
+		self.into_client()
+	}
+
+	/// Unplugs and destroys the controller, returning the client.
+	///
+	/// Unlike [`drop`](Self::drop) any error while unplugging is returned together with the client.
+	/// A controller which is not plugged in is destroyed without error.
+	#[inline]
+	pub fn try_drop(mut self) -> Result<CL, (Error, CL)> {
+		let result = if self.is_attached() { self.unplug() } else { Ok(()) };
+		let client = self.into_client();
+		match result {
+			Ok(()) => Ok(client),
+			Err(err) => Err((err, client)),
+		}
+	}
 
+	// Destroys the controller without unplugging it, returning the client.
+	#[inline]
+	fn into_client(mut self) -> CL {
+		self.poll_updates(true);
 		unsafe {
 			let client = (&self.client as *const CL).read();
 			ptr::drop_in_place(&mut self.event);
+			ptr::drop_in_place(&mut self.updates);
 			mem::forget(self);
 			client
 		}
 	}
 
 	/// Plugs the controller in.
+	///
+	/// The search for a free slot starts after the slot last handed out by the client,
+	/// or at the lowest slot unplugged since, see [`plugin_hint`](Self::plugin_hint).
 	#[inline(never)]
 	pub fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin_until(None, None)
+	}
+
+	/// Plugs the controller in, starting the search for a free slot at `start_serial`.
+	///
+	/// The driver has to be asked for every serial number in turn until a free one is found,
+	/// with many targets plugged in starting near a free slot avoids most of these requests.
+	/// The search wraps around to serial number 1 so any free slot is still found.
+	#[inline(never)]
+	pub fn plugin_hint(&mut self, start_serial: u32) -> Result<(), Error> {
+		self.plugin_until(Some(start_serial), None)
+	}
+
+	/// Plugs the controller in with the given serial number.
+	///
+	/// Unlike [`plugin`](Self::plugin) no free slot is searched for, only `serial_no` is tried.
+	/// This makes the serial number deterministic, eg. to reattach to a known slot.
+	///
+	/// Returns [`Error::SerialInUse`] if the serial number is taken by another target.
+	/// Serial numbers must be in the range `1..=65535`, other values return `WinError` with `ERROR_INVALID_PARAMETER`.
+	/// Other failures of the plugin request, eg. after a driver restart, are returned as `WinError` with [`Operation::Plugin`].
+	#[inline(never)]
+	pub fn plugin_with_serial(&mut self, serial_no: u32) -> Result<(), Error> {
+		self.strict_check(self.is_attached(), "plugin called on a controller which is already plugged in");
 		if self.is_attached() {
 			return Err(Error::AlreadyConnected);
 		}
+		if serial_no == 0 || serial_no > u16::MAX as u32 {
+			return Err(Error::win(winerror::ERROR_INVALID_PARAMETER, Operation::Plugin));
+		}
 
-		self.serial_no = unsafe {
-			let mut plugin = bus::PluginTarget::ds4_wired(1, self.id.vendor, self.id.product);
-			let device = self.client.borrow().device;
-
-			// Yes this is how the driver is implemented
-			while plugin.ioctl(device, self.event.handle).is_err() {
-				plugin.SerialNo += 1;
-				if plugin.SerialNo >= u16::MAX as u32 {
-					return Err(Error::NoFreeSlot);
-				}
+		let mut plugin = bus::PluginTarget::ds4_wired(serial_no, self.id.vendor, self.id.product);
+		let client = self.client.borrow();
+		let event = self.event.lock();
+		client.plug_unique(self.id, || {
+			match unsafe { plugin.ioctl(client.device, event.handle) } {
+				Ok(()) => Ok(serial_no),
+				// ViGEmBus reports a serial number which is already present as STATUS_INVALID_PARAMETER
+				Err(winerror::ERROR_INVALID_PARAMETER) => Err(Error::SerialInUse),
+				Err(err) => Err(Error::win(err, Operation::Plugin)),
 			}
+		})?;
 
-			plugin.SerialNo
-		};
+		self.ready = false;
+		self.serial_no = serial_no;
+		Ok(())
+	}
+
+	/// Plugs the controller in, giving up after `timeout` has elapsed.
+	///
+	/// Returns [`Error::Timeout`] if no free slot was found before the deadline,
+	/// or [`Error::NoFreeSlot`] if all slots were tried before the deadline.
+	#[inline(never)]
+	pub fn plugin_timeout(&mut self, timeout: time::Duration) -> Result<(), Error> {
+		let deadline = time::Instant::now().checked_add(timeout);
+		self.plugin_until(None, deadline)
+	}
+
+	#[inline]
+	fn plugin_until(&mut self, start: Option<u32>, deadline: Option<time::Instant>) -> Result<(), Error> {
+		self.strict_check(self.is_attached(), "plugin called on a controller which is already plugged in");
+		if self.is_attached() {
+			return Err(Error::AlreadyConnected);
+		}
+
+		self.ready = false;
+		let mut plugin = bus::PluginTarget::ds4_wired(1, self.id.vendor, self.id.product);
+		let client = self.client.borrow();
+		let event = self.event.lock();
+		self.serial_no = client.plug_unique(self.id, || client.scan_slots(start, deadline, |serial_no| {
+			plugin.SerialNo = serial_no;
+			unsafe { plugin.ioctl(client.device, event.handle) }.is_ok()
+		}))?;
 
 		Ok(())
 	}
 
 	/// Unplugs the controller.
+	///
+	/// Waits for the updates submitted with [`update_async`](Self::update_async) to complete first, invoking their callbacks.
 	#[inline(never)]
 	pub fn unplug(&mut self) -> Result<(), Error> {
+		// Even if not plugged in, see `Drop`
+		self.poll_updates(true);
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
-		unsafe {
+		let result = unsafe {
 			let mut unplug = bus::UnplugTarget::new(self.serial_no);
 			let device = self.client.borrow().device;
-			unplug.ioctl(device, self.event.handle)?;
+			unplug.ioctl(device, self.event.lock().handle).map_err(|err| Error::win(err, Operation::Unplug))
+		};
+		if let Err(err) = result {
+			log_warn!("unplugging target with serial number {} failed: {}", self.serial_no, err);
+			return Err(err);
 		}
 
+		log_debug!("unplugged target with serial number {}", self.serial_no);
+		self.client.borrow().target_removed(self.id, Some(self.serial_no));
 		self.serial_no = 0;
+		self.ready = false;
+		self.last_report = None;
+		self.buffer.Report = DS4Report::NEUTRAL;
 		Ok(())
 	}
 
+	/// Simulates the controller being disconnected for `off` and then reconnected.
+	///
+	/// Unplugs the controller, sleeps for `off` and plugs it in again, reusing the same serial number if it is still free.
+	/// If another target took the serial number in the meantime a free slot is searched as with [`plugin`](Self::plugin).
+	/// Waits until the reconnected controller is ready before returning.
+	///
+	/// Pending notification requests are aborted by the unplug and return [`Error::OperationAborted`], request a new notification after cycling.
+	/// A notification object which has no request pending while the controller is disconnected is not aborted,
+	/// if the serial number is reused its next request receives the notifications of the reconnected controller.
+	///
+	/// Returns [`Error::NotPluggedIn`] if the controller is not plugged in.
+	/// If plugging in again fails the controller is left unplugged.
+	///
+	/// ```no_run
+	/// # use std::time::Duration;
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// // Simulate a controller dropout of half a second
+	/// target.cycle(Duration::from_millis(500)).unwrap();
+	/// ```
+	#[inline(never)]
+	pub fn cycle(&mut self, off: time::Duration) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let serial_no = self.serial_no;
+		self.unplug()?;
+		thread::sleep(off);
+		match self.plugin_with_serial(serial_no) {
+			Err(Error::SerialInUse) => self.plugin()?,
+			result => result?,
+		}
+		self.wait_ready()
+	}
+
 	/// Waits until the virtual controller is ready.
 	///
 	/// Any updates submitted before the virtual controller is ready may return an error.
@@ -264,42 +1044,650 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 		unsafe {
 			let mut wait = bus::WaitDeviceReady::new(self.serial_no);
 			let device = self.client.borrow().device;
-			wait.ioctl(device, self.event.handle)?;
+			wait.ioctl(device, self.event.lock().handle).map_err(|err| Error::win(err, Operation::WaitReady))?;
 		}
 
+		self.ready = true;
 		Ok(())
 	}
 
-	/// Updates the virtual controller state.
+	/// Waits until the virtual controller is ready without blocking the async runtime.
+	///
+	/// The readiness request is run on the blocking thread pool of the tokio runtime with [`tokio::task::spawn_blocking`],
+	/// using a duplicate of the client's handle. Must be called from within a tokio runtime.
+	///
+	/// Returns the same errors as [`wait_ready`](Self::wait_ready),
+	/// and [`Error::OperationAborted`] if the runtime shuts down while waiting.
+	/// Dropping the future before it completes leaves the request running on the blocking thread pool until the controller is ready or unplugged.
+	///
+	/// ```no_run
+	/// # async fn example() -> Result<(), vigem_client::Error> {
+	/// let client = vigem_client::Client::connect()?;
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin()?;
+	/// target.wait_ready_async().await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "tokio")]
+	pub async fn wait_ready_async(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		self.client.borrow().wait_ready_async(self.serial_no).await?;
+		self.ready = true;
+		Ok(())
+	}
+
+	/// Waits until the virtual controller is ready, giving up after `timeout` has elapsed.
+	///
+	/// The timeout is rounded up to whole milliseconds.
+	/// Returns [`Error::Timeout`] if the controller was not ready in time.
 	#[inline(never)]
-	pub fn update(&mut self, report: &DS4Report) -> Result<(), Error> {
+	pub fn wait_ready_timeout(&mut self, timeout: time::Duration) -> Result<(), Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
 		unsafe {
-			let mut dsr = bus::DS4SubmitReport::new(self.serial_no, *report);
+			let mut wait = bus::WaitDeviceReady::new(self.serial_no);
 			let device = self.client.borrow().device;
-			dsr.ioctl(device, self.event.handle)?;
+			match wait.ioctl_timeout(device, self.event.lock().handle, event::timeout_ms(timeout)) {
+				Ok(()) => {
+					self.ready = true;
+					Ok(())
+				},
+				Err(winerror::WAIT_TIMEOUT) => Err(Error::Timeout),
+				Err(err) => Err(Error::win(err, Operation::WaitReady)),
+			}
 		}
+	}
+
+	/// Enables strict mode, which panics on misuse of the API in debug builds.
+	///
+	/// Misuse normally returns an error which is easily ignored, strict mode turns these sequencing bugs into panics
+	/// with a message describing the mistake:
+	///
+	/// * Plugging in a controller which is already plugged in.
+	/// * Updating a controller which is not plugged in.
+	/// * Updating a controller before [`wait_ready`](Self::wait_ready) or [`wait_ready_timeout`](Self::wait_ready_timeout) succeeded.
+	///
+	/// The checks are `debug_assert!`s, in release builds strict mode has no effect and the errors are returned as usual.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED).debug_strict();
+	/// target.plugin().unwrap();
+	/// // Panics in debug builds: update before wait_ready
+	/// let _ = target.update(&vigem_client::DS4Report::default());
+	/// ```
+	#[inline]
+	pub fn debug_strict(mut self) -> DualShock4Wired<CL> {
+		self.strict = true;
+		self
+	}
 
+	#[inline]
+	fn strict_check(&self, misuse: bool, message: &str) {
+		debug_assert!(!(self.strict && misuse), "DualShock4Wired: {}", message);
+	}
+
+	#[inline]
+	fn strict_check_update(&self) {
+		self.strict_check(!self.is_attached(), "update called on a controller which is not plugged in");
+		self.strict_check(!self.ready, "update called before wait_ready");
+	}
+
+	/// Sets the minimum interval between updates, a zero interval removes the limit.
+	///
+	/// When set, [`update`](Self::update), [`update_ex`](Self::update_ex) and [`update_batch`](Self::update_batch) skip reports submitted sooner than
+	/// `interval` after the previous one and return [`Error::TooFast`].
+	/// Some games behave erratically when updated faster than a real controller, see [`REPORT_INTERVAL`](Self::REPORT_INTERVAL).
+	/// Use a [`RateLimiter`] directly to sleep instead of skipping reports.
+	#[inline]
+	pub fn set_min_interval(&mut self, interval: time::Duration) {
+		self.limiter = if interval.is_zero() { None } else { Some(RateLimiter::new(interval)) };
+	}
+
+	// Checks the minimum interval, recording the submission if allowed.
+	#[inline]
+	fn check_rate(&mut self) -> Result<(), Error> {
+		match &mut self.limiter {
+			Some(limiter) => if limiter.try_acquire() { Ok(()) } else { Err(Error::TooFast) },
+			None => Ok(()),
+		}
+	}
+
+	/// Updates the virtual controller state.
+	///
+	/// The driver accepts the report whether or not an application has the controller open,
+	/// the submit request has no output telling if the report was read by anyone.
+	/// There is therefore no way to detect that nothing is listening, the closest signal is a game sending
+	/// rumble or lightbar output reports which are received with [`request_notification`](Self::request_notification).
+	///
+	/// This method blocks until the driver completes the submit request, it does not merely queue the report.
+	/// Once it returns successfully the driver has processed the report: it is the current state of the controller
+	/// and is returned to the next HID read of any application, so no separate synchronous update is needed for frame stepping.
+	///
+	/// Returns [`Error::OperationAborted`] if the request was cancelled while waiting, eg. because the target was removed.
+	/// Returns [`Error::DriverRestarted`] if the driver was restarted, the controller is then no longer plugged in.
+	#[inline(never)]
+	pub fn update(&mut self, report: &DS4Report) -> Result<(), Error> {
+		self.strict_check_update();
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		self.check_rate()?;
+		self.submit(report)
+	}
+
+	/// Updates the virtual controller state by modifying the last submitted report in place.
+	///
+	/// The controller keeps its submit request in a buffer, `f` modifies the report held there which is then submitted again.
+	/// The report starts out as the last one submitted with [`update`](Self::update), [`update_batch`](Self::update_batch),
+	/// [`reset`](Self::reset) or this method, or [`DS4Report::NEUTRAL`] if there was none since the target was plugged in.
+	/// Extended reports submitted with [`update_ex`](Self::update_ex) are not included.
+	///
+	/// This avoids building and copying a whole report for every update in high frequency loops which only change a few fields.
+	/// The saving is small compared to the cost of the request itself, measure before restructuring code around it.
+	///
+	/// The modification is kept even if the update fails or is skipped with [`Error::TooFast`],
+	/// the next call submits it unless it is replaced by another update first.
+	/// Returns the same errors as [`update`](Self::update).
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// for value in 0..=255 {
+	///     target.update_in_place(|report| report.set_trigger_r(value)).unwrap();
+	/// }
+	/// ```
+	#[inline(never)]
+	pub fn update_in_place<F: FnOnce(&mut DS4Report)>(&mut self, f: F) -> Result<(), Error> {
+		f(&mut self.buffer.Report);
+		self.strict_check_update();
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		self.check_rate()?;
+		self.submit_buffer()
+	}
+
+	/// Updates the virtual controller state without waiting for the update to complete.
+	///
+	/// The report is submitted to the driver and this method returns immediately, the caller can prepare the next report
+	/// or submit more updates while the driver is busy. `callback` receives the result of the update once it completed.
+	///
+	/// Callbacks are only invoked by [`poll_updates`](Self::poll_updates), on the thread calling it.
+	/// Nothing happens in the background, poll regularly (eg. once per frame) or the pending updates pile up.
+	/// [`unplug`](Self::unplug) and dropping the controller wait for the pending updates and invoke their callbacks.
+	///
+	/// # Ordering
+	///
+	/// * The updates are submitted to the driver in the order of the calls to this method.
+	/// * The callbacks are invoked in the same order, a callback is only invoked after the callbacks of all earlier updates.
+	/// * The driver does not guarantee to complete the requests in order. Every report is the complete controller state,
+	///   when an earlier report must never be applied after a later one wait for the earlier update with `poll_updates(true)` first.
+	/// * Blocking updates such as [`update`](Self::update) do not wait for the pending updates.
+	///
+	/// Returns an error without invoking the callback if the update could not be submitted,
+	/// the same errors as [`update`](Self::update) are possible.
+	/// Errors which occur while the update is pending, eg. [`Error::DriverRestarted`], are passed to the callback.
+	///
+	/// Asynchronous updates are not recorded in the [metrics](Self::metrics) and do not count as the last report for
+	/// [`update_if_changed`](Self::update_if_changed) and [`update_in_place`](Self::update_in_place).
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// for frame in 0..=255 {
+	///     let report = vigem_client::DS4ReportBuilder::new().trigger_r(frame).build();
+	///     target.update_async(&report, move |result| {
+	///         if let Err(err) = result {
+	///             eprintln!("frame {} failed: {}", frame, err);
+	///         }
+	///     }).unwrap();
+	///     target.poll_updates(false);
+	/// }
+	/// target.poll_updates(true);
+	/// ```
+	#[inline(never)]
+	pub fn update_async<F: FnOnce(Result<(), Error>) + Send + 'static>(&mut self, report: &DS4Report, callback: F) -> Result<(), Error> {
+		self.strict_check_update();
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+		self.check_rate()?;
+
+		self.last_report = None;
+		let device = self.client.borrow().device;
+		let updates = self.updates.get_mut().unwrap_or_else(PoisonError::into_inner);
+		let mut request = updates.spare.pop().unwrap_or_else(|| Box::new(bus::DS4SubmitReportAsync::new()));
+		request.request = bus::DS4SubmitReport::new(self.serial_no, *report);
+		match unsafe { request.start(device) } {
+			Ok(()) => {
+				updates.pending.push_back(PendingUpdate { request, callback: Box::new(callback) });
+				Ok(())
+			},
+			Err(err) => {
+				updates.spare.push(request);
+				Err(self.submit_error(err))
+			},
+		}
+	}
+
+	/// Invokes the callbacks of the completed updates submitted with [`update_async`](Self::update_async).
+	///
+	/// If `wait` is true blocks until all pending updates completed, else only the updates which already completed are handled.
+	/// The callbacks are invoked in submission order, an update which completed before an earlier pending one is handled later.
+	///
+	/// Returns the number of callbacks invoked.
+	#[inline(never)]
+	pub fn poll_updates(&mut self, wait: bool) -> usize {
+		let device = self.client.borrow().device;
+		let mut count = 0;
+		loop {
+			let updates = self.updates.get_mut().unwrap_or_else(PoisonError::into_inner);
+			let result = match updates.pending.front_mut() {
+				Some(update) => unsafe { update.request.poll(device, wait) },
+				None => break,
+			};
+			if result == Err(winerror::ERROR_IO_INCOMPLETE) {
+				break;
+			}
+			let PendingUpdate { request, callback } = updates.pending.pop_front().unwrap();
+			updates.spare.push(request);
+
+			let result = result.map_err(|err| self.submit_error(err));
+			callback(result);
+			count += 1;
+		}
+		count
+	}
+
+	/// Returns the number of updates submitted with [`update_async`](Self::update_async) whose callbacks were not invoked yet.
+	#[inline]
+	pub fn pending_updates(&self) -> usize {
+		self.updates.lock().unwrap_or_else(PoisonError::into_inner).pending.len()
+	}
+
+	/// Resets the virtual controller to its neutral state.
+	///
+	/// Submits a [default](DS4Report::default) report with the thumb sticks centered at `0x80`, the triggers at `0`,
+	/// no buttons pressed and the dpad released, eg. before unplugging so applications do not keep the last input as held.
+	/// Rumble and lightbar are set by the applications and are not affected.
+	///
+	/// The report is submitted regardless of the [minimum interval](Self::set_min_interval).
+	/// Does nothing if the controller is not plugged in.
+	#[inline(never)]
+	pub fn reset(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Ok(());
+		}
+		self.submit(&DS4Report::default())
+	}
+
+	#[inline]
+	fn submit(&mut self, report: &DS4Report) -> Result<(), Error> {
+		self.buffer.Report = *report;
+		self.submit_buffer()
+	}
+
+	// Submits the report held in the submit buffer.
+	fn submit_buffer(&mut self) -> Result<(), Error> {
+		self.last_report = None;
+		let start = self.metrics.map(|_| time::Instant::now());
+		self.buffer.SerialNo = self.serial_no;
+		let result = unsafe {
+			let device = self.client.borrow().device;
+			self.buffer.ioctl(device, self.event.lock().handle)
+		};
+		let result = result.map_err(|err| self.submit_error(err));
+		self.record_metrics(start, &result);
+		result?;
+
+		self.last_report = Some(self.buffer.Report);
 		Ok(())
 	}
 
+	#[inline]
+	fn record_metrics(&mut self, start: Option<time::Instant>, result: &Result<(), Error>) {
+		if let Some(metrics) = &mut self.metrics {
+			metrics.record(result);
+			if let Some(start) = start {
+				metrics.ioctl_time += start.elapsed();
+			}
+		}
+	}
+
+	/// Enables or disables tracking of the [`TargetMetrics`] of this controller.
+	///
+	/// Tracking is disabled by default, when enabled every submitted report increments a counter
+	/// and the time spent submitting is measured. Disabling tracking discards the counters.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.track_metrics(true);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	/// let _ = target.update(&vigem_client::DS4Report::default());
+	///
+	/// let metrics = target.metrics();
+	/// println!("{} updates, {} failed, {:?} submitting", metrics.updates, metrics.failed_updates, metrics.ioctl_time);
+	/// ```
+	#[inline]
+	pub fn track_metrics(&mut self, enabled: bool) {
+		match (enabled, self.metrics.is_some()) {
+			(true, false) => self.metrics = Some(TargetMetrics::default()),
+			(false, true) => self.metrics = None,
+			_ => (),
+		}
+	}
+
+	/// Returns the counters of the submitted reports, all zero if tracking is disabled.
+	#[inline]
+	pub fn metrics(&self) -> TargetMetrics {
+		self.metrics.unwrap_or_default()
+	}
+
+	/// Resets the counters of the submitted reports to zero, tracking remains enabled.
+	#[inline]
+	pub fn reset_metrics(&mut self) {
+		if let Some(metrics) = &mut self.metrics {
+			*metrics = TargetMetrics::default();
+		}
+	}
+
+	/// Updates the virtual controller state, returning how long the submission blocked.
+	///
+	/// The duration is the wall-clock time spent submitting the report and waiting for the driver to complete it.
+	/// Returns the same errors as [`update`](Self::update).
+	#[inline(never)]
+	pub fn update_timed(&mut self, report: &DS4Report) -> Result<time::Duration, Error> {
+		let start = time::Instant::now();
+		self.update(report)?;
+		Ok(start.elapsed())
+	}
+
+	/// Updates the virtual controller state if the report differs from the last submitted report.
+	///
+	/// Returns if the report was submitted.
+	/// The last report is forgotten when submitting an extended report or when the controller is unplugged.
+	#[inline(never)]
+	pub fn update_if_changed(&mut self, report: &DS4Report) -> Result<bool, Error> {
+		if self.is_attached() && self.last_report.as_ref() == Some(report) {
+			return Ok(false);
+		}
+		self.update(report)?;
+		Ok(true)
+	}
+
+	/// Updates the state of multiple virtual controllers at once.
+	///
+	/// All the reports are submitted before waiting for the driver to complete them together,
+	/// which saves a round trip per controller compared to calling [`update`](Self::update) for each.
+	///
+	/// The controllers are waited for in groups of at most 64 (`MAXIMUM_WAIT_OBJECTS`),
+	/// larger batches are split into multiple groups which are submitted one after the other.
+	///
+	/// Controllers of a client in [shared event mode](Client::shared_event_mode) cannot be waited for together,
+	/// their reports are submitted one after the other after the reports of the other controllers.
+	///
+	/// The [minimum interval](Self::set_min_interval) of every controller is applied, reports submitted too soon are skipped
+	/// and [`Error::TooFast`] is returned after the other reports were submitted.
+	///
+	/// Returns [`Error::NotPluggedIn`] without submitting anything if any of the controllers is not plugged in.
+	/// Otherwise returns the first error encountered, the remaining reports are still submitted.
+	/// Errors are handled for every controller as with [`update`](Self::update), eg. a controller removed by a driver restart
+	/// returns [`Error::DriverRestarted`] and is no longer plugged in while the other controllers are updated.
+	#[inline(never)]
+	pub fn update_batch(updates: &mut [(&mut DualShock4Wired<CL>, &DS4Report)]) -> Result<(), Error> {
+		for (target, _) in updates.iter() {
+			target.strict_check_update();
+		}
+		if updates.iter().any(|(target, _)| !target.is_attached()) {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let mut result = Ok(());
+		let (mut batched, shared): (Vec<_>, Vec<_>) = updates.iter_mut().partition(|(target, _)| !target.event.is_shared());
+		for chunk in batched.chunks_mut(MAXIMUM_WAIT_OBJECTS as usize) {
+			// Reports skipped by the rate limiter are not submitted
+			let mut allowed = Vec::with_capacity(chunk.len());
+			for update in chunk.iter_mut() {
+				match update.0.check_rate() {
+					Ok(()) => allowed.push(update),
+					Err(err) => result = result.and(Err(err)),
+				}
+			}
+
+			let mut entries: Vec<bus::DS4SubmitBatchEntry> = allowed.iter_mut().map(|(target, report)| {
+				target.last_report = None;
+				bus::DS4SubmitBatchEntry {
+					device: target.client.borrow().device,
+					event: target.event.handle(),
+					report: bus::DS4SubmitReport::new(target.serial_no, **report),
+				}
+			}).collect();
+			let results = unsafe { bus::ds4_submit_batch(&mut entries) };
+			for ((target, report), target_result) in allowed.into_iter().zip(results) {
+				let target_result = target_result.map_err(|err| target.submit_error(err));
+				target.record_metrics(None, &target_result);
+				if target_result.is_ok() {
+					target.buffer.Report = **report;
+					target.last_report = Some(**report);
+				}
+				result = result.and(target_result);
+			}
+		}
+		for (target, report) in shared {
+			result = result.and(target.check_rate().and_then(|()| target.submit(report)));
+		}
+		result
+	}
+
 	/// Updates the virtual controller state using the extended report.
+	///
+	/// Returns [`Error::DriverRestarted`] if the driver was restarted, the controller is then no longer plugged in.
 	#[inline(never)]
 	pub fn update_ex(&mut self, report: &DS4ReportEx) -> Result<(), Error> {
+		let report = match self.packet_counter {
+			Some(counter) => report.with_packet_counter(counter),
+			None => *report,
+		};
+		let result = self.submit_ex(&report);
+		if let (Ok(()), Some(counter)) = (&result, &mut self.packet_counter) {
+			*counter = (*counter + 1) & Self::PACKET_COUNTER_MAX;
+		}
+		result
+	}
+
+	// Submits the extended report as is, without the packet counter.
+	#[inline]
+	fn submit_ex(&mut self, report: &DS4ReportEx) -> Result<(), Error> {
+		self.strict_check_update();
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
+		self.check_rate()?;
 
-		unsafe {
+		self.last_report = None;
+		let start = self.metrics.map(|_| time::Instant::now());
+		let result = unsafe {
 			let mut dsr = bus::DS4SubmitReportEx::new(self.serial_no, *report);
 			let device = self.client.borrow().device;
-			dsr.ioctl(device, self.event.handle)?;
+			dsr.ioctl(device, self.event.lock().handle)
+		};
+		let result = result.map_err(|err| self.submit_error(err));
+		self.record_metrics(start, &result);
+		result
+	}
+
+	/// Largest value of the packet counter, it wraps around to `0` after this value.
+	pub const PACKET_COUNTER_MAX: u8 = 0x3F;
+
+	/// Sets the packet counter written into the next extended report, or `None` to submit the reports unchanged.
+	///
+	/// A real controller counts its reports in the upper 6 bits of the byte holding the PS and touchpad buttons.
+	/// The counter is disabled by default. Once enabled [`update_ex`](Self::update_ex) writes the counter into every report
+	/// and increments it after each successful submission. After [`PACKET_COUNTER_MAX`](Self::PACKET_COUNTER_MAX)
+	/// it wraps around to `0` like on a real controller, so games checking the counter see it increase by one modulo 64.
+	/// Values above the maximum are masked to 6 bits.
+	///
+	/// The counter overlaps [`DS4SpecialButtons::MIC_MUTE`], the bit cannot be submitted while the counter is enabled.
+	/// [`update`](Self::update), [`update_raw`](Self::update_raw) and [`mirror`](Self::mirror) submit their reports unchanged.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	///
+	/// assert_eq!(target.packet_counter(), None);
+	/// target.set_packet_counter(Some(62));
+	/// target.update_ex(&vigem_client::DS4ReportEx::default()).unwrap();
+	/// target.update_ex(&vigem_client::DS4ReportEx::default()).unwrap();
+	/// assert_eq!(target.packet_counter(), Some(0));
+	/// ```
+	#[inline]
+	pub fn set_packet_counter(&mut self, counter: Option<u8>) {
+		self.packet_counter = counter.map(|counter| counter & Self::PACKET_COUNTER_MAX);
+	}
+
+	/// Returns the packet counter written into the next extended report, `None` if disabled.
+	#[inline]
+	pub fn packet_counter(&self) -> Option<u8> {
+		self.packet_counter
+	}
+
+	/// Updates the virtual controller state using the extended report, returning how long the submission blocked.
+	///
+	/// The duration is the wall-clock time spent submitting the report and waiting for the driver to complete it.
+	/// Returns the same errors as [`update_ex`](Self::update_ex).
+	#[inline(never)]
+	pub fn update_ex_timed(&mut self, report: &DS4ReportEx) -> Result<time::Duration, Error> {
+		let start = time::Instant::now();
+		self.update_ex(report)?;
+		Ok(start.elapsed())
+	}
+
+	/// Updates the virtual controller state from a raw extended report.
+	///
+	/// The buffer is the DualShock4 USB HID input report `0x01` without the leading report id byte,
+	/// with the same layout as [`DS4ReportEx`]: sticks, buttons, triggers, timestamp, IMU, status and touch reports.
+	/// It is submitted as is without any validation.
+	///
+	/// Returns [`Error::DriverRestarted`] if the driver was restarted, the controller is then no longer plugged in.
+	#[inline(never)]
+	pub fn update_raw(&mut self, bytes: &[u8; 63]) -> Result<(), Error> {
+		// Safety: the extended report is a packed struct of 63 bytes where every bit pattern is valid
+		let report = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const DS4ReportEx) };
+		self.submit_ex(&report)
+	}
+
+	/// Mirrors a physical DualShock4 connected over USB, making this controller a transparent clone of it.
+	///
+	/// The loop reads the physical controller's input reports with `read` and submits them to this controller,
+	/// and writes the rumble and lightbar requested by the game back to the physical controller with `write`.
+	/// The callbacks abstract over the HID library, with [hidapi](https://crates.io/crates/hidapi) they wrap
+	/// `HidDevice::read_timeout` and `HidDevice::write` as in the example below.
+	/// With the `hidapi` feature enabled, `mirror_physical_ds4` implements exactly this loop.
+	///
+	/// `read` fills the buffer with an input report and returns its length, or `0` when no report was available.
+	/// It should block no longer than a few milliseconds as the game's output is only forwarded in between reads,
+	/// a connected controller sends a report every 4ms so a blocking read is usually fine.
+	/// The reports are parsed with [`DS4ReportEx::from_hid_input`], other reports such as Bluetooth input reports are ignored.
+	/// The output reports are encoded with [`DS4OutputReport::to_hid_output`].
+	///
+	/// The reports are submitted unchanged like with [`update_raw`](Self::update_raw), including the physical controller's packet counter.
+	/// Reports skipped with [`Error::TooFast`] because of [`set_min_interval`](Self::set_min_interval) are dropped.
+	///
+	/// The loop runs until an error occurs:
+	///
+	/// * Errors of `read` and `write`, eg. when the physical controller is disconnected, are returned as is.
+	/// * Errors of this controller are converted to [`io::Error`], use [`io::Error::get_ref`] to recover the [`Error`].
+	///   Unplugging this controller ends the loop with [`Error::NotPluggedIn`] or [`Error::OperationAborted`],
+	///   a driver restart with [`Error::DriverRestarted`].
+	///
+	/// To stop the loop from the outside, return an error from `read`.
+	///
+	/// ```ignore
+	/// use std::io;
+	///
+	/// let api = hidapi::HidApi::new().unwrap();
+	/// let device = api.open(0x054C, 0x09CC).unwrap();
+	///
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// let result = target.mirror(
+	///     |buffer| device.read_timeout(buffer, 4).map_err(|err| io::Error::other(err)),
+	///     |buffer| device.write(buffer).map(|_| ()).map_err(|err| io::Error::other(err)),
+	/// );
+	/// println!("mirroring stopped: {:?}", result);
+	/// ```
+	#[inline(never)]
+	pub fn mirror<R, W>(&mut self, mut read: R, mut write: W) -> io::Result<()>
+	where
+		R: FnMut(&mut [u8; 64]) -> io::Result<usize>,
+		W: FnMut(&[u8; 32]) -> io::Result<()>,
+	{
+		let mut notification = Box::pin(self.request_notification()?);
+		notification.as_mut().request();
+
+		let mut buffer = [0u8; 64];
+		loop {
+			let len = read(&mut buffer)?;
+			if let Some(report) = DS4ReportEx::from_hid_input(&buffer[..len.min(buffer.len())]) {
+				match self.submit_ex(&report) {
+					Ok(()) | Err(Error::TooFast) => (),
+					Err(err) => return Err(err.into()),
+				}
+			}
+
+			if let Some(output) = notification.as_mut().poll(false)? {
+				write(&output.to_hid_output())?;
+				notification.as_mut().request();
+			}
 		}
+	}
 
-		Ok(())
+	/// Returns if the client's connection to the ViGEmBus service is still usable.
+	///
+	/// Returns false after the driver was restarted.
+	#[inline]
+	pub fn is_client_valid(&self) -> bool {
+		self.client.borrow().is_valid()
+	}
+
+	// The target no longer exists when the driver was restarted.
+	#[inline]
+	fn submit_error(&mut self, err: u32) -> Error {
+		match err {
+			winerror::ERROR_DEVICE_REMOVED | winerror::ERROR_DEVICE_NOT_CONNECTED => {
+				log_error!("target with serial number {} was removed by a driver restart", self.serial_no);
+				self.client.borrow().target_removed(self.id, None);
+				self.serial_no = 0;
+				self.ready = false;
+				Error::DriverRestarted
+			},
+			// The request was cancelled while waiting, eg. the target was removed
+			winerror::ERROR_OPERATION_ABORTED => Error::OperationAborted,
+			err => {
+				let err = Error::win(err, Operation::Submit);
+				log_warn!("submitting a report to target with serial number {} failed: {}", self.serial_no, err);
+				err
+			},
+		}
 	}
 
 	/// Request notification.
@@ -308,16 +1696,170 @@ impl<CL: Borrow<Client>> DualShock4Wired<CL> {
 	///
 	/// Do not create more than one request notification per target.
 	/// Notifications may get lost or received by one or more listeners.
+	///
+	/// The client's handle is duplicated for the notification request, see [`Client::try_clone`] for the errors this may return.
 	#[inline(never)]
 	pub fn request_notification(&mut self) -> Result<DSRequestNotification, Error> {
 		if !self.is_attached() {
 			return Err(Error::NotPluggedIn);
 		}
 
-		let client = self.client.borrow().try_clone()?;
+		let client = Arc::new(self.client.borrow().try_clone()?);
+		Ok(DSRequestNotification::new(client, self.serial_no))
+	}
+
+	/// Request notification borrowing the client instead of duplicating its handle.
+	///
+	/// The request is tied to the lifetime of this target and is meant for inline polling loops.
+	/// Use [`request_notification`](Self::request_notification) for a request which can be moved to another thread.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	///
+	/// let mut notification = Box::pin(target.request_notification_borrowed().unwrap());
+	/// notification.as_mut().request();
+	/// if let Ok(Some(report)) = notification.as_mut().poll(true) {
+	///     println!("{:?}", report);
+	/// }
+	/// ```
+	#[inline(never)]
+	pub fn request_notification_borrowed(&mut self) -> Result<DSRequestNotificationRef<'_>, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
 		let ds4rn = bus::RequestNotification::new(bus::RequestNotificationVariant::DS4(bus::DS4RequestNotification::new(self.serial_no)));
+		Ok(DSRequestNotificationRef { client: self.client.borrow(), ds4rn, _unpin: marker::PhantomPinned })
+	}
+
+	/// Reads the latest output report without waiting.
+	///
+	/// Requests notifications until no more are queued by the driver and returns the last one received,
+	/// or `None` if the game has not sent anything new since the last request.
+	/// The final pending request is cancelled before returning.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	///
+	/// if let Some(report) = target.read_output().unwrap() {
+	///     println!("rumble: {} {}", report.large_motor, report.small_motor);
+	/// }
+	/// ```
+	#[inline(never)]
+	pub fn read_output(&mut self) -> Result<Option<DS4OutputReport>, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		let device = self.client.borrow().device;
+		let mut ds4rn = bus::RequestNotification::new(bus::RequestNotificationVariant::DS4(bus::DS4RequestNotification::new(self.serial_no)));
+		let mut latest = None;
+		let result = loop {
+			unsafe { ds4rn.ioctl(device); }
+			let result = unsafe { ds4rn.poll(device, false) };
+			match poll_result(&mut ds4rn, result) {
+				Ok(Some(report)) => latest = Some(report),
+				Ok(None) => break Ok(()),
+				Err(err) => break Err(err),
+			}
+		};
+
+		// The request must not outlive the overlapped structure
+		let completed = unsafe { ds4rn.cancel(device) }.map_err(|err| Error::win(err, Operation::Notify))?;
+		// The last request may have completed before it was cancelled, its report is the latest
+		if completed {
+			if let Some(report) = poll_result(&mut ds4rn, Ok(()))? {
+				latest = Some(report);
+			}
+		}
+		result.map(|_| latest)
+	}
+
+	/// Plugs the controller in and returns a guard which unplugs it again when dropped.
+	///
+	/// Unlike dropping the controller, any error while unplugging is not ignored but passed to `on_error`.
+	/// The guard dereferences to the controller so all its methods remain available.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	///
+	/// let mut guard = target.plugin_guard(|err| eprintln!("unplug failed: {}", err)).unwrap();
+	/// guard.wait_ready().unwrap();
+	/// guard.update(&vigem_client::DS4Report::default()).unwrap();
+	/// ```
+	#[inline(never)]
+	pub fn plugin_guard<'a, F: FnMut(Error) + 'a>(&'a mut self, on_error: F) -> Result<PluggedTarget<'a, CL>, Error> {
+		self.plugin()?;
+		Ok(PluggedTarget { target: self, on_error: Box::new(on_error) })
+	}
+}
+
+/// Guard returned by [`DualShock4Wired::plugin_guard`].
+///
+/// Unplugs the controller when dropped, forwarding any error to the callback given at creation.
+pub struct PluggedTarget<'a, CL: Borrow<Client>> {
+	target: &'a mut DualShock4Wired<CL>,
+	on_error: Box<dyn FnMut(Error) + 'a>,
+}
+
+impl<'a, CL: Borrow<Client>> ops::Deref for PluggedTarget<'a, CL> {
+	type Target = DualShock4Wired<CL>;
+	#[inline]
+	fn deref(&self) -> &DualShock4Wired<CL> {
+		self.target
+	}
+}
+impl<'a, CL: Borrow<Client>> ops::DerefMut for PluggedTarget<'a, CL> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut DualShock4Wired<CL> {
+		self.target
+	}
+}
+
+impl<'a, CL: Borrow<Client>> fmt::Debug for PluggedTarget<'a, CL> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_tuple("PluggedTarget").field(&self.target).finish()
+	}
+}
+
+impl<'a, CL: Borrow<Client>> Drop for PluggedTarget<'a, CL> {
+	fn drop(&mut self) {
+		// The controller may have been unplugged through the guard already
+		if self.target.is_attached() {
+			if let Err(err) = self.target.unplug() {
+				(self.on_error)(err);
+			}
+		}
+	}
+}
+
+impl DualShock4Wired<Arc<Client>> {
+	/// Request notification sharing the client.
+	///
+	/// Unlike [`request_notification`](Self::request_notification) the client's handle is not duplicated,
+	/// the notification request holds on to the shared client instead.
+	/// This avoids creating a handle per listener when managing many targets with a single client.
+	///
+	/// ```no_run
+	/// use std::sync::Arc;
+	/// let client = Arc::new(vigem_client::Client::connect().unwrap());
+	///
+	/// let mut target = vigem_client::DualShock4Wired::new(client.clone(), vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// let notification = target.request_notification_shared().unwrap();
+	/// ```
+	#[inline(never)]
+	pub fn request_notification_shared(&mut self) -> Result<DSRequestNotification, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
 
-		Ok(DSRequestNotification { client, ds4rn, _unpin: marker::PhantomPinned })
+		Ok(DSRequestNotification::new(self.client.clone(), self.serial_no))
 	}
 }
 
@@ -327,6 +1869,8 @@ impl<CL: Borrow<Client>> fmt::Debug for DualShock4Wired<CL> {
 			.field("serial_no", &self.serial_no)
 			.field("vendor_id", &self.id.vendor)
 			.field("product_id", &self.id.product)
+			.field("ready", &self.ready)
+			.field("strict", &self.strict)
 			.finish()
 	}
 }
@@ -334,6 +1878,7 @@ impl<CL: Borrow<Client>> fmt::Debug for DualShock4Wired<CL> {
 impl<CL: Borrow<Client>> Drop for DualShock4Wired<CL> {
 	#[inline]
 	fn drop(&mut self) {
+		// Also waits for the pending updates, which must complete before their buffers are freed
 		let _ = self.unplug();
 	}
 }
@@ -23,36 +23,73 @@ use std::{
 /// let buttons = buttons | DS4Buttons::SHOULDER_LEFT;
 /// # assert_eq!(u16::from(buttons), DS4Buttons::THUMB_RIGHT | DS4Buttons::CROSS | DpadDirection::South as u16 | DS4Buttons::SHOULDER_LEFT);
 /// ```
-#[derive(Copy, Clone, Eq, PartialEq)]
+///
+/// # Bit layout
+///
+/// The raw representation, see [`DS4Buttons::from_bits`] and [`DS4Buttons::bits`], is a stable part of the API
+/// and matches the DualShock4 HID report:
+///
+/// | Bits  | Meaning                                                          |
+/// |-------|------------------------------------------------------------------|
+/// | 0..4  | D-Pad direction, `0` is North going clockwise to `7` North-West and `8` is neutral, see [`DpadDirection`] |
+/// | 4     | Square ([`DS4Buttons::SQUARE`])                                  |
+/// | 5     | Cross ([`DS4Buttons::CROSS`])                                    |
+/// | 6     | Circle ([`DS4Buttons::CIRCLE`])                                  |
+/// | 7     | Triangle ([`DS4Buttons::TRIANGLE`])                              |
+/// | 8     | L1 ([`DS4Buttons::SHOULDER_LEFT`])                               |
+/// | 9     | R1 ([`DS4Buttons::SHOULDER_RIGHT`])                              |
+/// | 10    | L2 ([`DS4Buttons::TRIGGER_LEFT`])                                |
+/// | 11    | R2 ([`DS4Buttons::TRIGGER_RIGHT`])                               |
+/// | 12    | Share ([`DS4Buttons::SHARE`])                                    |
+/// | 13    | Options ([`DS4Buttons::OPTIONS`])                                |
+/// | 14    | L3 ([`DS4Buttons::THUMB_LEFT`])                                  |
+/// | 15    | R3 ([`DS4Buttons::THUMB_RIGHT`])                                 |
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[must_use = "This struct serves as a builder,
               and must be consumed by calling into() with the `DS4Report`/`DS4ReportEx` structs or directly with their respective builders"]
 pub struct DS4Buttons(pub(super) u16);
 
 impl Debug for DS4Buttons {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NAMES: [(u16, &str); 12] = [
+            (DS4Buttons::SQUARE, "square"),
+            (DS4Buttons::CROSS, "cross"),
+            (DS4Buttons::CIRCLE, "circle"),
+            (DS4Buttons::TRIANGLE, "triangle"),
+            (DS4Buttons::SHOULDER_LEFT, "l1"),
+            (DS4Buttons::SHOULDER_RIGHT, "r1"),
+            (DS4Buttons::TRIGGER_LEFT, "l2"),
+            (DS4Buttons::TRIGGER_RIGHT, "r2"),
+            (DS4Buttons::SHARE, "share"),
+            (DS4Buttons::OPTIONS, "options"),
+            (DS4Buttons::THUMB_LEFT, "l3"),
+            (DS4Buttons::THUMB_RIGHT, "r3"),
+        ];
         f.debug_struct("DS4Buttons")
-            .field("thumb_right", &(self.0 & DS4Buttons::THUMB_RIGHT != 0))
-            .field("thumb_left", &(self.0 & DS4Buttons::THUMB_LEFT != 0))
-            .field("options", &(self.0 & DS4Buttons::OPTIONS != 0))
-            .field("share", &(self.0 & DS4Buttons::SHARE != 0))
-            .field("trigger_right", &(self.0 & DS4Buttons::TRIGGER_RIGHT != 0))
-            .field("trigger_left", &(self.0 & DS4Buttons::TRIGGER_LEFT != 0))
-            .field(
-                "shoulder_right",
-                &(self.0 & DS4Buttons::SHOULDER_RIGHT != 0),
-            )
-            .field("shoulder_left", &(self.0 & DS4Buttons::SHOULDER_LEFT != 0))
-            .field("triangle", &(self.0 & DS4Buttons::TRIANGLE != 0))
-            .field("circle", &(self.0 & DS4Buttons::CIRCLE != 0))
-            .field("cross", &(self.0 & DS4Buttons::CROSS != 0))
-            .field("square", &(self.0 & DS4Buttons::SQUARE != 0))
-            .field(
-                "dpad",
-                &DpadDirection::try_from(self.0 & 0xF).unwrap_or(DpadDirection::None),
-            )
+            .field("pressed", &Pressed(&NAMES, self.0))
+            .field("dpad", &self.get_dpad())
             .finish()
     }
 }
+
+// Formats the names of the set flags as a list, eg. `[cross, l1]`.
+struct Pressed<'a>(&'a [(u16, &'static str)], u16);
+
+impl<'a> Debug for Pressed<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        struct Name(&'static str);
+        impl Debug for Name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+        f.debug_list()
+            .entries(self.0.iter().filter(|&&(flag, _)| self.1 & flag != 0).map(|&(_, name)| Name(name)))
+            .finish()
+    }
+}
+
 impl Default for DS4Buttons {
     #[inline]
     fn default() -> Self {
@@ -70,8 +107,12 @@ impl DS4Buttons {
     /// Share button.
     pub const SHARE: u16 = 1 << 12;
     /// Trigger right button (R2).
+    ///
+    /// The digital bit of the right trigger, independent of the analog `trigger_r` value of the report.
     pub const TRIGGER_RIGHT: u16 = 1 << 11;
     /// Trigger left button (L2).
+    ///
+    /// The digital bit of the left trigger, independent of the analog `trigger_l` value of the report.
     pub const TRIGGER_LEFT: u16 = 1 << 10;
     /// Shoulder right button (R1).
     pub const SHOULDER_RIGHT: u16 = 1 << 9;
@@ -86,8 +127,10 @@ impl DS4Buttons {
     pub const CROSS: u16 = 1 << 5;
     /// Square button.
     pub const SQUARE: u16 = 1 << 4;
+    /// D-Pad direction bits.
+    const DPAD_MASK: u16 = 0xF;
     /// D-Pad neutral position.
-    const DPAD_NONE: u16 = 0x8; // 1 << 3
+    pub(crate) const DPAD_NONE: u16 = 0x8; // 1 << 3
     /// D-Pad North-West direction.
     const DPAD_NORTHWEST: u16 = 0x7;
     /// D-Pad West direction.
@@ -136,6 +179,29 @@ impl DS4Buttons {
         DS4Buttons::default()
     }
 
+    /// Create a [`DS4Buttons`] instance from its raw bits, see the [bit layout](DS4Buttons#bit-layout).
+    ///
+    /// Note that a D-Pad value of `0` is the North direction, the neutral D-Pad is [`DpadDirection::None`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4Buttons, DpadDirection};
+    /// let buttons = DS4Buttons::from_bits(DS4Buttons::CROSS | DpadDirection::None as u16);
+    /// assert_eq!(buttons, DS4Buttons::new().cross(true));
+    /// assert_eq!(buttons.bits() & DS4Buttons::CROSS, DS4Buttons::CROSS);
+    /// ```
+    #[inline]
+    pub const fn from_bits(bits: u16) -> Self {
+        DS4Buttons(bits)
+    }
+
+    /// Get the raw bits, see the [bit layout](DS4Buttons#bit-layout).
+    #[inline]
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
     /// Set the thumb right button state.
     #[inline]
     pub fn thumb_right(mut self, enable: bool) -> Self {
@@ -208,6 +274,49 @@ impl DS4Buttons {
         self
     }
 
+    /// Set the L1 button state, same as [`shoulder_left`](Self::shoulder_left).
+    #[inline]
+    pub fn l1(self, enable: bool) -> Self {
+        self.shoulder_left(enable)
+    }
+
+    /// Set the R1 button state, same as [`shoulder_right`](Self::shoulder_right).
+    #[inline]
+    pub fn r1(self, enable: bool) -> Self {
+        self.shoulder_right(enable)
+    }
+
+    /// Set the digital L2 button state, same as [`trigger_left`](Self::trigger_left).
+    ///
+    /// The DualShock4 reports the triggers twice, as this digital bit and as the analog `trigger_l` value of the report.
+    /// A real controller sets the bit as soon as the trigger is pulled slightly, the driver passes both through as they are.
+    /// Games reading buttons only see the bit while games reading axes only see the analog value, set both together to support either,
+    /// eg. with [`DS4ReportBuilder::trigger_l_full`](crate::DS4ReportBuilder::trigger_l_full).
+    #[inline]
+    pub fn l2(self, enable: bool) -> Self {
+        self.trigger_left(enable)
+    }
+
+    /// Set the digital R2 button state, same as [`trigger_right`](Self::trigger_right).
+    ///
+    /// See [`l2`](Self::l2) for how the digital bit relates to the analog `trigger_r` value of the report.
+    #[inline]
+    pub fn r2(self, enable: bool) -> Self {
+        self.trigger_right(enable)
+    }
+
+    /// Set the L3 button state, pressing the left thumb stick, same as [`thumb_left`](Self::thumb_left).
+    #[inline]
+    pub fn l3(self, enable: bool) -> Self {
+        self.thumb_left(enable)
+    }
+
+    /// Set the R3 button state, pressing the right thumb stick, same as [`thumb_right`](Self::thumb_right).
+    #[inline]
+    pub fn r3(self, enable: bool) -> Self {
+        self.thumb_right(enable)
+    }
+
     /// Set the triangle button state.
     #[inline]
     pub fn triangle(mut self, enable: bool) -> Self {
@@ -245,12 +354,27 @@ impl DS4Buttons {
     }
 
     /// Set the D-Pad direction, with the [`DpadDirection`] enum.
+    ///
+    /// Replaces any previously set direction.
     #[inline]
     pub fn dpad(mut self, dpad: DpadDirection) -> Self {
-        self.0 ^= DpadDirection::None as u16;
-        self.0 |= dpad as u16;
+        self.0 = (self.0 & !DS4Buttons::DPAD_MASK) | dpad as u16;
         self
     }
+
+    /// Get the D-Pad direction.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4Buttons, DpadDirection};
+    /// let buttons = DS4Buttons::new().dpad(DpadDirection::NorthWest).dpad(DpadDirection::East);
+    /// assert_eq!(buttons.get_dpad(), DpadDirection::East);
+    /// ```
+    #[inline]
+    pub fn get_dpad(&self) -> DpadDirection {
+        DpadDirection::try_from(self.0 & DS4Buttons::DPAD_MASK).unwrap_or(DpadDirection::None)
+    }
 }
 
 impl BitOr<DpadDirection> for DS4Buttons {
@@ -308,6 +432,42 @@ pub enum DpadDirection {
     None = DS4Buttons::DPAD_NONE,
 }
 
+impl Default for DpadDirection {
+    #[inline]
+    fn default() -> Self {
+        DpadDirection::None
+    }
+}
+
+impl DpadDirection {
+    /// Get the direction from the state of four separate D-Pad buttons, as reported by most gamepad APIs.
+    ///
+    /// Opposite directions pressed together cancel each other out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DpadDirection;
+    /// assert_eq!(DpadDirection::from_buttons(true, false, false, true), DpadDirection::NorthEast);
+    /// assert_eq!(DpadDirection::from_buttons(true, true, false, false), DpadDirection::None);
+    /// ```
+    pub fn from_buttons(up: bool, down: bool, left: bool, right: bool) -> Self {
+        let vertical = up as i8 - down as i8;
+        let horizontal = right as i8 - left as i8;
+        match (vertical, horizontal) {
+            (1, 0) => DpadDirection::North,
+            (1, 1) => DpadDirection::NorthEast,
+            (0, 1) => DpadDirection::East,
+            (-1, 1) => DpadDirection::SouthEast,
+            (-1, 0) => DpadDirection::South,
+            (-1, -1) => DpadDirection::SouthWest,
+            (0, -1) => DpadDirection::West,
+            (1, -1) => DpadDirection::NorthWest,
+            _ => DpadDirection::None,
+        }
+    }
+}
+
 #[doc(hidden)]
 impl TryFrom<u16> for DpadDirection {
     type Error = ();
@@ -343,24 +503,45 @@ impl TryFrom<u16> for DpadDirection {
 /// let buttons = buttons | DS4SpecialButtons::TOUCHPAD;
 /// # assert_eq!(u8::from(buttons), DS4SpecialButtons::MIC_MUTE | DS4SpecialButtons::PS_HOME | DS4SpecialButtons::TOUCHPAD);
 /// ```
-#[derive(Copy, Clone, Eq, PartialEq)]
+///
+/// # Bit layout
+///
+/// The raw representation, see [`DS4SpecialButtons::from_bits`] and [`DS4SpecialButtons::bits`], is a stable part of the API:
+///
+/// | Bits  | Meaning                                           |
+/// |-------|---------------------------------------------------|
+/// | 0     | PS Home ([`DS4SpecialButtons::PS_HOME`])          |
+/// | 1     | Touchpad click ([`DS4SpecialButtons::TOUCHPAD`])  |
+/// | 2     | Mic mute ([`DS4SpecialButtons::MIC_MUTE`])        |
+/// | 3..8  | Unused, should be zero                            |
+///
+/// On a real controller bits 2..8 hold a packet counter instead,
+/// see [`DualShock4Wired::set_packet_counter`](crate::DualShock4Wired::set_packet_counter).
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[must_use = "This struct serves as a builder,
               and must be consumed by calling into() with the `DS4Report`/`DS4ReportEx` structs or directly with their respective builders"]
 pub struct DS4SpecialButtons(pub(super) u8);
 
 impl Debug for DS4SpecialButtons {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NAMES: [(u16, &str); 3] = [
+            (DS4SpecialButtons::PS_HOME as u16, "ps_home"),
+            (DS4SpecialButtons::TOUCHPAD as u16, "touchpad"),
+            (DS4SpecialButtons::MIC_MUTE as u16, "mic_mute"),
+        ];
         f.debug_struct("DS4SpecialButtons")
-            .field("mic_mute", &(self.0 & DS4SpecialButtons::MIC_MUTE != 0))
-            .field("touchpad", &(self.0 & DS4SpecialButtons::TOUCHPAD != 0))
-            .field("ps_home", &(self.0 & DS4SpecialButtons::PS_HOME != 0))
+            .field("pressed", &Pressed(&NAMES, self.0 as u16))
             .finish()
     }
 }
 
 impl DS4SpecialButtons {
+    /// Mic mute button.
     pub const MIC_MUTE: u8 = 1 << 2;
+    /// Touchpad click.
     pub const TOUCHPAD: u8 = 1 << 1;
+    /// PS Home button.
     pub const PS_HOME: u8 = 1 << 0;
 }
 
@@ -410,6 +591,47 @@ impl DS4SpecialButtons {
         }
         self
     }
+
+    /// Create a [`DS4SpecialButtons`] instance from its raw bits, see the [bit layout](DS4SpecialButtons#bit-layout).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4SpecialButtons;
+    /// let touch = DS4SpecialButtons::from_bits(DS4SpecialButtons::TOUCHPAD);
+    /// let home = DS4SpecialButtons::new().ps_home(true);
+    /// let buttons = DS4SpecialButtons::from_bits(touch.bits() | home.bits());
+    /// assert!(buttons.get_touchpad() && buttons.get_ps_home());
+    /// assert!(!buttons.get_mic_mute());
+    /// ```
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Self {
+        DS4SpecialButtons(bits)
+    }
+
+    /// Get the raw bits, see the [bit layout](DS4SpecialButtons#bit-layout).
+    #[inline]
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Get the mic mute button state.
+    #[inline]
+    pub fn get_mic_mute(&self) -> bool {
+        self.0 & DS4SpecialButtons::MIC_MUTE != 0
+    }
+
+    /// Get the touchpad button state.
+    #[inline]
+    pub fn get_touchpad(&self) -> bool {
+        self.0 & DS4SpecialButtons::TOUCHPAD != 0
+    }
+
+    /// Get the PS Home button state.
+    #[inline]
+    pub fn get_ps_home(&self) -> bool {
+        self.0 & DS4SpecialButtons::PS_HOME != 0
+    }
 }
 
 impl BitOr<u8> for DS4SpecialButtons {
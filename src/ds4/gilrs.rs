@@ -0,0 +1,89 @@
+//! Conversion from [`gilrs`](::gilrs) gamepad states.
+
+use super::{DS4Buttons, DS4Report, DS4ReportBuilder, DS4SpecialButtons, DpadDirection};
+
+use ::gilrs::{Axis, Button, Gamepad};
+
+/// Maps a gilrs button value in `0.0..=1.0` to the DualShock4 `0..=255` trigger range.
+fn trigger_to_u8(value: f32) -> u8 {
+    let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+    (value * 255.0).round() as u8
+}
+
+impl DS4Report {
+    /// Creates a report from the cached state of a [`gilrs::Gamepad`](::gilrs::Gamepad).
+    ///
+    /// Stick axes are mapped from gilrs's `-1.0..=1.0` range with [`axis_from_f32`](crate::axis_from_f32).
+    /// The Y axes are inverted since gilrs reports up as positive while the DualShock4 reports up as `0`.
+    /// Analog triggers use the `0.0..=1.0` value of [`Button::LeftTrigger2`] and [`Button::RightTrigger2`].
+    ///
+    /// The buttons are mapped as follows:
+    ///
+    /// | gilrs                                   | DualShock4               |
+    /// |-----------------------------------------|--------------------------|
+    /// | `Axis::LeftStickX` / `LeftStickY`       | `thumb_lx` / `thumb_ly`  |
+    /// | `Axis::RightStickX` / `RightStickY`     | `thumb_rx` / `thumb_ry`  |
+    /// | `Button::South`                         | Cross                    |
+    /// | `Button::East`                          | Circle                   |
+    /// | `Button::North`                         | Triangle                 |
+    /// | `Button::West`                          | Square                   |
+    /// | `Button::LeftTrigger`                   | L1 (`shoulder_left`)     |
+    /// | `Button::RightTrigger`                  | R1 (`shoulder_right`)    |
+    /// | `Button::LeftTrigger2`                  | L2 (`trigger_left` and `trigger_l`) |
+    /// | `Button::RightTrigger2`                 | R2 (`trigger_right` and `trigger_r`) |
+    /// | `Button::Select`                        | Share                    |
+    /// | `Button::Start`                         | Options                  |
+    /// | `Button::Mode`                          | PS                       |
+    /// | `Button::LeftThumb` / `RightThumb`      | L3 / R3                  |
+    /// | `Button::DPad*`                         | D-Pad, including diagonals |
+    ///
+    /// Touchpad and mic mute have no gilrs equivalent and are left released.
+    /// Where your conventions differ, adjust the returned report through a [`DS4ReportBuilder`]
+    /// or build one from scratch with [`Gamepad::is_pressed`] and [`Gamepad::value`].
+    pub fn from_gilrs(gamepad: &Gamepad) -> Self {
+        let pressed = |button| gamepad.is_pressed(button);
+        let trigger = |button| {
+            gamepad
+                .button_data(button)
+                .map_or(0, |data| trigger_to_u8(data.value()))
+        };
+
+        let buttons = DS4Buttons::new()
+            .cross(pressed(Button::South))
+            .circle(pressed(Button::East))
+            .triangle(pressed(Button::North))
+            .square(pressed(Button::West))
+            .shoulder_left(pressed(Button::LeftTrigger))
+            .shoulder_right(pressed(Button::RightTrigger))
+            .trigger_left(pressed(Button::LeftTrigger2))
+            .trigger_right(pressed(Button::RightTrigger2))
+            .share(pressed(Button::Select))
+            .options(pressed(Button::Start))
+            .thumb_left(pressed(Button::LeftThumb))
+            .thumb_right(pressed(Button::RightThumb))
+            .dpad(DpadDirection::from_buttons(
+                pressed(Button::DPadUp),
+                pressed(Button::DPadDown),
+                pressed(Button::DPadLeft),
+                pressed(Button::DPadRight),
+            ));
+
+        DS4ReportBuilder::new()
+            .thumb_lx_f32(gamepad.value(Axis::LeftStickX))
+            .thumb_ly_f32(-gamepad.value(Axis::LeftStickY))
+            .thumb_rx_f32(gamepad.value(Axis::RightStickX))
+            .thumb_ry_f32(-gamepad.value(Axis::RightStickY))
+            .buttons(buttons)
+            .special(DS4SpecialButtons::new().ps_home(pressed(Button::Mode)))
+            .trigger_l(trigger(Button::LeftTrigger2))
+            .trigger_r(trigger(Button::RightTrigger2))
+            .build()
+    }
+}
+
+impl From<&Gamepad<'_>> for DS4Report {
+    #[inline]
+    fn from(gamepad: &Gamepad<'_>) -> Self {
+        DS4Report::from_gilrs(gamepad)
+    }
+}
@@ -0,0 +1,62 @@
+//! Pass-through of a physical DualShock4 opened with [`hidapi`](::hidapi).
+
+use std::borrow::Borrow;
+use std::io;
+
+use ::hidapi::{HidDevice, HidError};
+
+use crate::{Client, DualShock4Wired};
+
+/// Time in milliseconds a single read waits for an input report before the game's output is checked.
+///
+/// A connected controller sends an input report every 4ms.
+const READ_TIMEOUT_MS: i32 = 4;
+
+fn hid_error(err: HidError) -> io::Error {
+    io::Error::other(err)
+}
+
+/// Mirrors a physical DualShock4 controller with the virtual `target`.
+///
+/// This creates a transparent virtual clone of the physical controller:
+/// its input reports are forwarded to `target` and the rumble and lightbar requested by the game are written back to `device`.
+/// The loop is built on [`DualShock4Wired::mirror`], the target must be plugged in before calling this function.
+///
+/// # Input reports
+///
+/// Only USB input reports (report id `0x01`) are forwarded, see [`DS4ReportEx::from_hid_input`](crate::DS4ReportEx::from_hid_input).
+/// The controller has to be connected with a cable; reports of controllers connected over Bluetooth are ignored.
+/// The reports are submitted unchanged, including the touchpad, motion sensors and the physical controller's packet counter.
+/// Reads time out after a few milliseconds so the game's output is forwarded even while the controller sends no reports.
+///
+/// # Errors
+///
+/// The loop runs until an error occurs:
+///
+/// * When the physical controller is disconnected, reading or writing `device` fails and the [`HidError`] is returned
+///   wrapped in an [`io::Error`] of kind [`io::ErrorKind::Other`], use [`io::Error::get_ref`] to recover it.
+/// * When the virtual controller is unplugged the loop ends with [`Error::NotPluggedIn`](crate::Error::NotPluggedIn)
+///   or [`Error::OperationAborted`](crate::Error::OperationAborted), when the ViGEmBus driver is restarted with
+///   [`Error::DriverRestarted`](crate::Error::DriverRestarted). These are converted to [`io::Error`] as well,
+///   use [`io::Error::get_ref`] to recover the [`Error`](crate::Error).
+///
+/// In either case the other controller is left as is, unplug the target if it should disappear with the physical controller.
+///
+/// ```no_run
+/// let api = hidapi::HidApi::new().unwrap();
+/// let device = api.open(0x054C, 0x09CC).unwrap();
+///
+/// let client = vigem_client::Client::connect().unwrap();
+/// let mut target = vigem_client::DualShock4Wired::new(&client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+///
+/// let result = vigem_client::mirror_physical_ds4(&mut target, &device);
+/// println!("mirroring stopped: {:?}", result);
+/// ```
+pub fn mirror_physical_ds4<CL: Borrow<Client>>(target: &mut DualShock4Wired<CL>, device: &HidDevice) -> io::Result<()> {
+    target.mirror(
+        |buffer| device.read_timeout(buffer, READ_TIMEOUT_MS).map_err(hid_error),
+        |buffer| device.write(buffer).map(|_| ()).map_err(hid_error),
+    )
+}
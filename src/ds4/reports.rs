@@ -2,7 +2,7 @@
 
 use super::{DS4Buttons, DS4SpecialButtons};
 
-use std::fmt;
+use std::{fmt, ptr};
 
 /// DualShock4 HID basic input report.
 ///
@@ -12,7 +12,24 @@ use std::fmt;
 /// [`DS4ReportEx`].
 ///
 /// It shouldn't be constructed directly, but using [`DS4ReportBuilder`].
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+///
+/// The debug output shows the decoded fields, the pressed buttons are listed by name:
+///
+/// ```rust
+/// # use vigem_client::{DS4ReportBuilder, DS4Buttons, DpadDirection};
+/// let report = DS4ReportBuilder::new()
+///     .buttons(DS4Buttons::new().cross(true).l1(true).dpad(DpadDirection::South))
+///     .trigger_r(255)
+///     .build();
+/// assert_eq!(
+///     format!("{:?}", report),
+///     "DS4Report { thumb_lx: 128, thumb_ly: 128, thumb_rx: 128, thumb_ry: 128, \
+///     buttons: DS4Buttons { pressed: [cross, l1], dpad: South }, \
+///     special: DS4SpecialButtons { pressed: [] }, trigger_l: 0, trigger_r: 255 }",
+/// );
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DS4Report {
     thumb_lx: u8,
@@ -24,27 +41,152 @@ pub struct DS4Report {
     trigger_l: u8,
     trigger_r: u8,
 }
+
+impl fmt::Debug for DS4Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DS4Report")
+            .field("thumb_lx", &self.thumb_lx)
+            .field("thumb_ly", &self.thumb_ly)
+            .field("thumb_rx", &self.thumb_rx)
+            .field("thumb_ry", &self.thumb_ry)
+            .field("buttons", &DS4Buttons(self.buttons))
+            .field("special", &DS4SpecialButtons(self.special))
+            .field("trigger_l", &self.trigger_l)
+            .field("trigger_r", &self.trigger_r)
+            .finish()
+    }
+}
+
 impl Default for DS4Report {
+    /// Returns [`DS4Report::NEUTRAL`].
+    #[inline]
     fn default() -> Self {
-        DS4Report {
-            thumb_lx: 0x80,
-            thumb_ly: 0x80,
-            thumb_rx: 0x80,
-            thumb_ry: 0x80,
-            buttons: u16::from(DS4Buttons::default()),
-            special: u8::from(DS4SpecialButtons::default()),
-            trigger_l: 0,
-            trigger_r: 0,
-        }
+        DS4Report::NEUTRAL
     }
 }
 
+impl DS4Report {
+    /// A report with the sticks centered, the triggers released and no buttons pressed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4Report;
+    /// static IDLE: DS4Report = DS4Report::NEUTRAL;
+    /// assert_eq!(IDLE, DS4Report::default());
+    /// ```
+    pub const NEUTRAL: DS4Report = DS4Report {
+        thumb_lx: 0x80,
+        thumb_ly: 0x80,
+        thumb_rx: 0x80,
+        thumb_ry: 0x80,
+        buttons: DS4Buttons::DPAD_NONE,
+        special: 0,
+        trigger_l: 0,
+        trigger_r: 0,
+    };
+
+    // Getters, the builder is used to create reports.
+
+    /// Returns the left thumb stick X axis.
+    #[inline]
+    pub fn thumb_lx(&self) -> u8 {
+        self.thumb_lx
+    }
+    /// Returns the left thumb stick Y axis.
+    #[inline]
+    pub fn thumb_ly(&self) -> u8 {
+        self.thumb_ly
+    }
+    /// Returns the right thumb stick X axis.
+    #[inline]
+    pub fn thumb_rx(&self) -> u8 {
+        self.thumb_rx
+    }
+    /// Returns the right thumb stick Y axis.
+    #[inline]
+    pub fn thumb_ry(&self) -> u8 {
+        self.thumb_ry
+    }
+    /// Returns the buttons.
+    #[inline]
+    pub fn buttons(&self) -> DS4Buttons {
+        DS4Buttons(self.buttons)
+    }
+    /// Returns the special buttons.
+    #[inline]
+    pub fn special(&self) -> DS4SpecialButtons {
+        DS4SpecialButtons(self.special)
+    }
+    /// Returns the left trigger.
+    #[inline]
+    pub fn trigger_l(&self) -> u8 {
+        self.trigger_l
+    }
+    /// Returns the right trigger.
+    #[inline]
+    pub fn trigger_r(&self) -> u8 {
+        self.trigger_r
+    }
+
+    // In-place setters for updating a report without rebuilding it,
+    // see `DualShock4Wired::update_in_place`.
+
+    /// Sets the left thumb stick X axis.
+    #[inline]
+    pub fn set_thumb_lx(&mut self, value: u8) {
+        self.thumb_lx = value;
+    }
+    /// Sets the left thumb stick Y axis.
+    #[inline]
+    pub fn set_thumb_ly(&mut self, value: u8) {
+        self.thumb_ly = value;
+    }
+    /// Sets the right thumb stick X axis.
+    #[inline]
+    pub fn set_thumb_rx(&mut self, value: u8) {
+        self.thumb_rx = value;
+    }
+    /// Sets the right thumb stick Y axis.
+    #[inline]
+    pub fn set_thumb_ry(&mut self, value: u8) {
+        self.thumb_ry = value;
+    }
+    /// Sets the buttons.
+    #[inline]
+    pub fn set_buttons(&mut self, value: DS4Buttons) {
+        self.buttons = value.into();
+    }
+    /// Sets the special buttons.
+    #[inline]
+    pub fn set_special(&mut self, value: DS4SpecialButtons) {
+        self.special = value.into();
+    }
+    /// Sets the left trigger.
+    #[inline]
+    pub fn set_trigger_l(&mut self, value: u8) {
+        self.trigger_l = value;
+    }
+    /// Sets the right trigger.
+    #[inline]
+    pub fn set_trigger_r(&mut self, value: u8) {
+        self.trigger_r = value;
+    }
+}
+
+/// Largest X coordinate of a [`DS4TouchPoint`], the right edge of the touchpad.
+pub const DS4_TOUCHPAD_WIDTH: u16 = 1920;
+/// Largest Y coordinate of a [`DS4TouchPoint`], the bottom edge of the touchpad.
+pub const DS4_TOUCHPAD_HEIGHT: u16 = 942;
+
 /// DualShock4 touch point.
 /// The touch point is in the range 0..1920 for the X coordinate and 0..942 for the Y coordinate.
 ///
 /// It is recommended to use [`DS4TouchPoint::new`] to create a new touch point,
 /// which can then be used to create a [`DS4TouchReport`].
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "DS4TouchPointFields", into = "DS4TouchPointFields"))]
 #[repr(C, packed)]
 pub struct DS4TouchPoint {
     /// bit 7 is 0 if finger is down
@@ -58,6 +200,7 @@ impl fmt::Debug for DS4TouchPoint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("DS4TouchPoint")
             .field("active", &self.is_active())
+            .field("id", &self.id())
             .field("x", &self.x())
             .field("y", &self.y())
             .finish()
@@ -74,11 +217,12 @@ impl DS4TouchPoint {
     /// ```rust
     /// # use vigem_client::DS4TouchPoint;
     /// let point = DS4TouchPoint::new(1920, 942);
+    /// assert_eq!(format!("{:?}", point), "DS4TouchPoint { active: true, id: 0, x: 1920, y: 942 }");
     /// ```
     #[inline]
     pub fn new(x: u16, y: u16) -> Self {
-        let x = x.min(1920);
-        let y = y.min(942);
+        let x = x.min(DS4_TOUCHPAD_WIDTH);
+        let y = y.min(DS4_TOUCHPAD_HEIGHT);
         DS4TouchPoint {
             contact: 0,
             x_lo: (x & 0xFF) as u8,
@@ -90,25 +234,136 @@ impl DS4TouchPoint {
         }
     }
 
+    /// Create a new active touch point from coordinates normalized to `0.0..=1.0`.
+    ///
+    /// `(0.0, 0.0)` is the top left and `(1.0, 1.0)` the bottom right corner of the touchpad,
+    /// scaled to [`DS4_TOUCHPAD_WIDTH`] and [`DS4_TOUCHPAD_HEIGHT`].
+    /// Out of range coordinates are clamped to the edges, NaN maps to `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4TouchPoint;
+    /// let point = DS4TouchPoint::from_normalized(0.5, 1.5);
+    /// # assert_eq!(point, DS4TouchPoint::new(960, 942));
+    /// ```
+    #[inline]
+    pub fn from_normalized(x: f32, y: f32) -> Self {
+        fn scale(value: f32, max: u16) -> u16 {
+            let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+            (value * max as f32).round() as u16
+        }
+        DS4TouchPoint::new(scale(x, DS4_TOUCHPAD_WIDTH), scale(y, DS4_TOUCHPAD_HEIGHT))
+    }
+
     /// Create a new inactive touch point.
     #[inline]
     pub fn inactive() -> Self {
         DS4TouchPoint::default()
     }
 
+    /// Set the finger tracking id of the touch point.
+    ///
+    /// The id is in the range 0..128 and will be wrapped to this range.
+    /// A finger should keep the same id for as long as it touches the touchpad, see [`DS4TouchIdAllocator`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4TouchPoint;
+    /// let point = DS4TouchPoint::new(960, 471).with_id(5);
+    /// # assert_eq!(point.id(), 5);
+    /// ```
+    #[inline]
+    pub fn with_id(mut self, id: u8) -> Self {
+        self.contact = (self.contact & (1 << 7)) | (id & 0x7F);
+        self
+    }
+
+    /// Get the finger tracking id of the touch point.
+    #[inline]
+    pub fn id(&self) -> u8 {
+        self.contact & 0x7F
+    }
+
+    /// Set if the finger touches the touchpad at this point.
+    ///
+    /// Only the contact bit is changed, the coordinates and the id are kept.
+    /// This allows a precise sequence of frames, eg. a tap is a report with an active point
+    /// followed by a report with the same point made inactive.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4TouchPoint;
+    /// let down = DS4TouchPoint::new(960, 471).with_id(3);
+    /// let up = down.with_active(false);
+    /// assert!(down.is_active());
+    /// assert!(!up.is_active());
+    /// assert_eq!(up.id(), 3);
+    /// ```
+    #[inline]
+    pub fn with_active(mut self, active: bool) -> Self {
+        if active {
+            self.contact &= !(1 << 7);
+        } else {
+            self.contact |= 1 << 7;
+        }
+        self
+    }
+
     /// Returns if the touch point is active.
-    fn is_active(&self) -> bool {
+    #[inline]
+    pub fn is_active(&self) -> bool {
         self.contact & (1 << 7) == 0
     }
 
     /// Get the X coordinate of the touch point.
     fn x(&self) -> u16 {
-        ((self.x_hi_y_lo & 0xF0) as u16) << 4 | self.x_lo as u16
+        ((self.x_hi_y_lo & 0xF) as u16) << 8 | self.x_lo as u16
     }
 
     /// Get the Y coordinate of the touch point.
     fn y(&self) -> u16 {
-        (self.y_hi as u16) << 4 | ((self.x_hi_y_lo & 0xF) as u16)
+        (self.y_hi as u16) << 4 | (self.x_hi_y_lo >> 4) as u16
+    }
+}
+
+// Serialized representation of a touch point with decoded coordinates.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DS4TouchPointFields {
+    active: bool,
+    #[serde(default)]
+    id: u8,
+    x: u16,
+    y: u16,
+}
+
+#[cfg(feature = "serde")]
+impl From<DS4TouchPoint> for DS4TouchPointFields {
+    #[inline]
+    fn from(point: DS4TouchPoint) -> Self {
+        DS4TouchPointFields {
+            active: point.is_active(),
+            id: point.id(),
+            x: point.x(),
+            y: point.y(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<DS4TouchPointFields> for DS4TouchPoint {
+    // Not clamped, the fields must round-trip to the same packed bytes.
+    #[inline]
+    fn from(fields: DS4TouchPointFields) -> Self {
+        DS4TouchPoint {
+            contact: if fields.active { 0 } else { 1 << 7 } | (fields.id & 0x7F),
+            x_lo: (fields.x & 0xFF) as u8,
+            x_hi_y_lo: ((fields.x & 0xF00) >> 8 | (fields.y & 0xF) << 4) as u8,
+            y_hi: (fields.y >> 4) as u8,
+        }
     }
 }
 
@@ -116,20 +371,26 @@ impl Default for DS4TouchPoint {
     /// Create an inactive touch point.
     #[inline]
     fn default() -> Self {
-        DS4TouchPoint {
-            contact: 1 << 7,
-            x_lo: 0,
-            x_hi_y_lo: 0,
-            y_hi: 0,
-        }
+        DS4TouchPoint::INACTIVE
     }
 }
 
+impl DS4TouchPoint {
+    // An inactive touch point, usable in const contexts.
+    const INACTIVE: DS4TouchPoint = DS4TouchPoint {
+        contact: 1 << 7,
+        x_lo: 0,
+        x_hi_y_lo: 0,
+        y_hi: 0,
+    };
+}
+
 /// DualShock4 touch report.
 /// A touch report contains two touch points, which can be created using [`DS4TouchPoint::new`].
 ///
 /// It is recommended to use [`DS4TouchReport::new`] to create a new touch report.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DS4TouchReport {
     timestamp: u8,
@@ -160,16 +421,161 @@ impl DS4TouchReport {
     }
 }
 
+impl DS4TouchReport {
+    /// Create a new touch report with two active touch points.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4TouchReport, DS4TouchPoint};
+    /// let report = DS4TouchReport::two_points(1, DS4TouchPoint::new(400, 300).with_id(0), DS4TouchPoint::new(1500, 600).with_id(1));
+    /// ```
+    #[inline]
+    pub fn two_points(counter: u8, first: DS4TouchPoint, second: DS4TouchPoint) -> Self {
+        DS4TouchReport {
+            timestamp: counter,
+            points: [first, second],
+        }
+    }
+
+    /// Set the packet counter of the touch report.
+    #[inline]
+    pub fn with_counter(mut self, counter: u8) -> Self {
+        self.timestamp = counter;
+        self
+    }
+}
+
 impl Default for DS4TouchReport {
     #[inline]
     fn default() -> Self {
+        DS4TouchReport::INACTIVE
+    }
+}
+
+impl DS4TouchReport {
+    // A touch report without active points, usable in const contexts.
+    const INACTIVE: DS4TouchReport = DS4TouchReport {
+        timestamp: 0,
+        points: [DS4TouchPoint::INACTIVE; 2],
+    };
+}
+
+/// A builder for [`DS4TouchReport`].
+///
+/// Points are added in order with [`point`](Self::point), a touch report holds at most two points
+/// and any further points are ignored. Slots without a point are inactive.
+///
+/// # Examples
+///
+/// ```rust
+/// # use vigem_client::{DS4TouchReportBuilder, DS4TouchPoint};
+/// let report = DS4TouchReportBuilder::new()
+///     .counter(7)
+///     .point(DS4TouchPoint::new(400, 300).with_id(0))
+///     .point(DS4TouchPoint::new(1500, 600).with_id(1))
+///     .build();
+/// # assert_eq!(report, vigem_client::DS4TouchReport::two_points(7, DS4TouchPoint::new(400, 300).with_id(0), DS4TouchPoint::new(1500, 600).with_id(1)));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+#[must_use = "This struct serves as a builder,
+              and must be consumed by calling either .build() or .into()"]
+pub struct DS4TouchReportBuilder {
+    counter: u8,
+    points: [DS4TouchPoint; 2],
+    num_points: usize,
+}
+
+impl DS4TouchReportBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the packet counter, it should be incremented for each new report.
+    #[inline]
+    pub fn counter(mut self, counter: u8) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    /// Add a touch point, points after the second are ignored.
+    #[inline]
+    pub fn point(mut self, point: DS4TouchPoint) -> Self {
+        if let Some(slot) = self.points.get_mut(self.num_points) {
+            *slot = point;
+            self.num_points += 1;
+        }
+        self
+    }
+
+    /// Returns the number of points added, at most two.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.num_points
+    }
+
+    /// Returns if no points were added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.num_points == 0
+    }
+
+    /// Build the touch report.
+    #[inline]
+    pub fn build(self) -> DS4TouchReport {
         DS4TouchReport {
-            timestamp: 0,
-            points: [DS4TouchPoint::default(); 2],
+            timestamp: self.counter,
+            points: self.points,
         }
     }
 }
 
+impl From<DS4TouchReportBuilder> for DS4TouchReport {
+    #[inline]
+    fn from(builder: DS4TouchReportBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Allocates finger tracking ids for [`DS4TouchPoint`]s.
+///
+/// The touchpad tracks fingers by id, a new id should be allocated every time a finger touches the touchpad
+/// and kept for as long as the finger stays down. Ids are in the range 0..128 and wrap around.
+///
+/// # Examples
+///
+/// ```rust
+/// # use vigem_client::{DS4TouchIdAllocator, DS4TouchPoint, DS4TouchReport};
+/// let mut ids = DS4TouchIdAllocator::new();
+/// let first = ids.next_id();
+/// let second = ids.next_id();
+///
+/// // Keep the same ids on every frame while the fingers stay down
+/// let report = DS4TouchReport::two_points(0, DS4TouchPoint::new(400, 300).with_id(first), DS4TouchPoint::new(1500, 600).with_id(second));
+/// # assert_ne!(first, second);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DS4TouchIdAllocator {
+    next: u8,
+}
+
+impl DS4TouchIdAllocator {
+    /// Create a new allocator starting at id 0.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate the next finger id.
+    #[inline]
+    pub fn next_id(&mut self) -> u8 {
+        let id = self.next;
+        self.next = (self.next + 1) & 0x7F;
+        id
+    }
+}
+
 /// DualShock4 v1 complete HID Input report.
 /// It is used to update the controller state with the [`crate::DualShock4Wired::update_ex`] method.
 ///
@@ -177,7 +583,8 @@ impl Default for DS4TouchReport {
 /// the gyroscope and accelerometer data.
 ///
 /// It shouldn't be constructed directly, but using [`DS4ReportExBuilder`].
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C, packed)]
 pub struct DS4ReportEx {
     thumb_lx: u8,
@@ -196,17 +603,198 @@ pub struct DS4ReportEx {
     accel_x: i16,
     accel_y: i16,
     accel_z: i16,
+    #[cfg_attr(feature = "serde", serde(skip))]
     reserved2: [u8; 5],
     status: u16,
+    #[cfg_attr(feature = "serde", serde(skip))]
     reserved3: u8,
     num_touch_reports: u8,              // 0x00 to 0x03 (USB max)
     touch_reports: [DS4TouchReport; 3], // Most recent touch report first
+    #[cfg_attr(feature = "serde", serde(skip))]
     reserved: [u8; 3],
 }
 
 // Assert that the struct has the correct size.
 const _: [(); 63] = [(); std::mem::size_of::<DS4ReportEx>()];
 
+impl DS4ReportEx {
+    // Getters, the builder is used to set the fields.
+    // Fields are copied out since the struct is packed.
+
+    /// Returns the left thumb stick X axis.
+    #[inline]
+    pub fn thumb_lx(&self) -> u8 {
+        self.thumb_lx
+    }
+    /// Returns the left thumb stick Y axis.
+    #[inline]
+    pub fn thumb_ly(&self) -> u8 {
+        self.thumb_ly
+    }
+    /// Returns the right thumb stick X axis.
+    #[inline]
+    pub fn thumb_rx(&self) -> u8 {
+        self.thumb_rx
+    }
+    /// Returns the right thumb stick Y axis.
+    #[inline]
+    pub fn thumb_ry(&self) -> u8 {
+        self.thumb_ry
+    }
+    /// Returns the buttons.
+    #[inline]
+    pub fn buttons(&self) -> DS4Buttons {
+        DS4Buttons(self.buttons)
+    }
+    /// Returns the special buttons.
+    #[inline]
+    pub fn special(&self) -> DS4SpecialButtons {
+        DS4SpecialButtons(self.special)
+    }
+    /// Returns the packet counter, the upper 6 bits of the special buttons byte.
+    ///
+    /// See [`DualShock4Wired::set_packet_counter`](crate::DualShock4Wired::set_packet_counter).
+    #[inline]
+    pub fn packet_counter(&self) -> u8 {
+        self.special >> 2
+    }
+    // Replaces the packet counter, keeping the PS and touchpad buttons.
+    #[inline]
+    pub(crate) fn with_packet_counter(mut self, counter: u8) -> Self {
+        self.special = (self.special & 0x03) | (counter << 2);
+        self
+    }
+    /// Returns the left trigger.
+    #[inline]
+    pub fn trigger_l(&self) -> u8 {
+        self.trigger_l
+    }
+    /// Returns the right trigger.
+    #[inline]
+    pub fn trigger_r(&self) -> u8 {
+        self.trigger_r
+    }
+    /// Returns the timestamp.
+    #[inline]
+    pub fn timestamp(&self) -> u16 {
+        self.timestamp
+    }
+    /// Returns the temperature.
+    #[inline]
+    pub fn temp(&self) -> u8 {
+        self.temp
+    }
+    /// Returns the gyroscope X axis.
+    #[inline]
+    pub fn gyro_x(&self) -> i16 {
+        self.gyro_x
+    }
+    /// Returns the gyroscope Y axis.
+    #[inline]
+    pub fn gyro_y(&self) -> i16 {
+        self.gyro_y
+    }
+    /// Returns the gyroscope Z axis.
+    #[inline]
+    pub fn gyro_z(&self) -> i16 {
+        self.gyro_z
+    }
+    /// Returns the accelerometer X axis.
+    #[inline]
+    pub fn accel_x(&self) -> i16 {
+        self.accel_x
+    }
+    /// Returns the accelerometer Y axis.
+    #[inline]
+    pub fn accel_y(&self) -> i16 {
+        self.accel_y
+    }
+    /// Returns the accelerometer Z axis.
+    #[inline]
+    pub fn accel_z(&self) -> i16 {
+        self.accel_z
+    }
+    /// Returns the battery status.
+    #[inline]
+    pub fn status(&self) -> DS4Status {
+        DS4Status(self.status)
+    }
+    /// Returns the number of active touch reports, in the range 0..=3.
+    #[inline]
+    pub fn num_touch_reports(&self) -> u8 {
+        self.num_touch_reports
+    }
+    /// Returns the touch reports, with the most recent report first.
+    ///
+    /// Only the first [`num_touch_reports`](Self::num_touch_reports) reports are active.
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4ReportExBuilder, DS4TouchReport, DS4TouchPoint};
+    /// let touch = DS4TouchReport::new(0, Some(DS4TouchPoint::new(100, 200)), None);
+    /// let report = DS4ReportExBuilder::new()
+    ///     .thumb_lx(0x10)
+    ///     .trigger_r(0xff)
+    ///     .touch_reports(Some(touch), None, None)
+    ///     .build();
+    /// assert_eq!(report.thumb_lx(), 0x10);
+    /// assert_eq!(report.trigger_r(), 0xff);
+    /// assert_eq!(report.num_touch_reports(), 1);
+    /// assert_eq!(report.touch_reports()[0], touch);
+    /// ```
+    #[inline]
+    pub fn touch_reports(&self) -> [DS4TouchReport; 3] {
+        self.touch_reports
+    }
+
+    /// Returns the packed bytes of the report as submitted to the driver.
+    ///
+    /// This is the DualShock4 USB HID input report `0x01` without the leading report id byte,
+    /// the bus request header holding the size and serial number is not included either.
+    /// A captured report of a real controller matches these bytes after dropping its first byte,
+    /// and the bytes can be submitted again with [`DualShock4Wired::update_raw`](crate::DualShock4Wired::update_raw).
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4ReportEx;
+    /// let report = DS4ReportEx::default();
+    /// assert_eq!(report.as_bytes().len(), 63);
+    /// assert_eq!(report.as_bytes()[..4], [0x80; 4]);
+    /// ```
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 63] {
+        // Safety: the struct is packed to 63 bytes without padding and has an alignment of 1
+        unsafe { &*(self as *const DS4ReportEx as *const [u8; 63]) }
+    }
+
+    /// Parses an input report read from a physical DualShock4 connected over USB.
+    ///
+    /// The buffer is the USB HID input report `0x01` including the leading report id byte, as read from the device.
+    /// The 63 bytes after the report id have the same layout as this report and are copied as is, see [`as_bytes`](Self::as_bytes).
+    ///
+    /// Returns `None` if the buffer does not start with report id `0x01` or is shorter than 64 bytes.
+    /// Bluetooth input reports (`0x11`) are not recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4ReportEx;
+    /// let mut buffer = [0u8; 64];
+    /// buffer[0] = 0x01;
+    /// buffer[1..].copy_from_slice(DS4ReportEx::NEUTRAL.as_bytes());
+    /// assert_eq!(DS4ReportEx::from_hid_input(&buffer), Some(DS4ReportEx::NEUTRAL));
+    /// assert_eq!(DS4ReportEx::from_hid_input(&buffer[..32]), None);
+    /// ```
+    #[inline]
+    pub fn from_hid_input(report: &[u8]) -> Option<DS4ReportEx> {
+        match report {
+            [0x01, data @ ..] if data.len() >= 63 => {
+                // Safety: the struct is packed to 63 bytes and every bit pattern is valid
+                Some(unsafe { ptr::read_unaligned(data.as_ptr() as *const DS4ReportEx) })
+            },
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Debug for DS4ReportEx {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let buttons = DS4Buttons(self.buttons);
@@ -246,34 +834,51 @@ impl fmt::Debug for DS4ReportEx {
 }
 
 impl Default for DS4ReportEx {
+    /// Returns [`DS4ReportEx::NEUTRAL`].
+    #[inline]
     fn default() -> Self {
-        DS4ReportEx {
-            thumb_lx: 0x80,
-            thumb_ly: 0x80,
-            thumb_rx: 0x80,
-            thumb_ry: 0x80,
-            buttons: DS4Buttons::default().into(),
-            special: DS4SpecialButtons::default().into(),
-            trigger_l: 0,
-            trigger_r: 0,
-            timestamp: 0,
-            temp: 0,
-            gyro_x: 0,
-            gyro_y: 0,
-            gyro_z: 0,
-            accel_x: 0,
-            accel_y: 0,
-            accel_z: 0,
-            reserved2: [0; 5],
-            status: DS4Status::default().into(),
-            reserved3: 0,
-            num_touch_reports: 0,
-            touch_reports: [DS4TouchReport::default(); 3],
-            reserved: [0; 3],
-        }
+        DS4ReportEx::NEUTRAL
     }
 }
 
+impl DS4ReportEx {
+    /// A report with the sticks centered, the triggers released, no buttons pressed,
+    /// no motion, a wired controller with a full battery and no fingers on the touchpad.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4ReportEx, BatteryStatus};
+    /// static IDLE: DS4ReportEx = DS4ReportEx::NEUTRAL;
+    /// assert_eq!(IDLE, DS4ReportEx::default());
+    /// assert_eq!(IDLE.status().battery_status(), BatteryStatus::Full);
+    /// ```
+    pub const NEUTRAL: DS4ReportEx = DS4ReportEx {
+        thumb_lx: 0x80,
+        thumb_ly: 0x80,
+        thumb_rx: 0x80,
+        thumb_ry: 0x80,
+        buttons: DS4Buttons::DPAD_NONE,
+        special: 0,
+        trigger_l: 0,
+        trigger_r: 0,
+        timestamp: 0,
+        temp: 0,
+        gyro_x: 0,
+        gyro_y: 0,
+        gyro_z: 0,
+        accel_x: 0,
+        accel_y: 0,
+        accel_z: 0,
+        reserved2: [0; 5],
+        status: DS4Status::CABLE_STATE | DS4Status::BATTERY_FULL,
+        reserved3: 0,
+        num_touch_reports: 0,
+        touch_reports: [DS4TouchReport::INACTIVE; 3],
+        reserved: [0; 3],
+    };
+}
+
 // Builders for DS4 reports.
 
 /// Battery status of the controller, mainly used for [`DS4Status`].
@@ -291,6 +896,13 @@ pub enum BatteryStatus {
     NotCharging,
 }
 
+impl Default for BatteryStatus {
+    #[inline]
+    fn default() -> Self {
+        BatteryStatus::Full
+    }
+}
+
 #[doc(hidden)]
 impl From<BatteryStatus> for u16 {
     #[inline]
@@ -309,10 +921,12 @@ impl From<u16> for BatteryStatus {
     #[inline]
     fn from(status: u16) -> Self {
         match status & 0xF {
+            capacity @ 0..=10 => BatteryStatus::Charging(capacity as u8),
             DS4Status::BATTERY_FULL => BatteryStatus::Full,
             DS4Status::NOT_CHARGING => BatteryStatus::NotCharging,
             DS4Status::CHARGE_ERROR => BatteryStatus::Error,
-            capacity => BatteryStatus::Charging(capacity as u8),
+            // 12 and 13 are not used by the controller
+            _ => BatteryStatus::Error,
         }
     }
 }
@@ -326,9 +940,9 @@ pub struct DS4Status(u16);
 
 impl fmt::Debug for DS4Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let battery_status = BatteryStatus::from(self.0 & 0xF);
+        let battery_status = self.battery_status();
         f.debug_struct("DS4Status")
-            .field("cable_state", &(self.0 & DS4Status::CABLE_STATE != 0))
+            .field("cable_state", &self.is_wired())
             .field("dongle_state", &(self.0 & DS4Status::_DONGLE_STATE != 0))
             .field("battery_status", &battery_status)
             .finish()
@@ -364,11 +978,81 @@ impl DS4Status {
     pub fn with_battery_status(status: BatteryStatus) -> Self {
         DS4Status(DS4Status::CABLE_STATE | u16::from(status))
     }
+
+    /// Decode the battery status, the inverse of [`DS4Status::with_battery_status`].
+    ///
+    /// `Charging(10)` is a fully charged battery which is still charging,
+    /// while [`BatteryStatus::Full`] means charging has completed.
+    /// The unused raw values 12 and 13 decode as [`BatteryStatus::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4Status, BatteryStatus};
+    /// let status = DS4Status::with_battery_status(BatteryStatus::Charging(8));
+    /// assert_eq!(status.battery_status(), BatteryStatus::Charging(8));
+    ///
+    /// let status = DS4Status::from(DS4Status::CABLE_STATE | DS4Status::BATTERY_FULL);
+    /// assert_eq!(status.battery_status(), BatteryStatus::Full);
+    /// ```
+    #[inline]
+    pub fn battery_status(&self) -> BatteryStatus {
+        BatteryStatus::from(self.0)
+    }
+
+    /// Returns if the cable is connected, [`DS4Status::CABLE_STATE`] is set.
+    ///
+    /// Always true for a status created with [`DS4Status::with_battery_status`].
+    #[inline]
+    pub fn is_wired(&self) -> bool {
+        self.0 & DS4Status::CABLE_STATE != 0
+    }
+
+    /// Returns if the battery is charging, the battery status is [`BatteryStatus::Charging`] with the cable connected.
+    ///
+    /// A full battery, [`BatteryStatus::Full`], has completed charging and is not charging.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4Status, BatteryStatus};
+    /// assert!(DS4Status::with_battery_status(BatteryStatus::Charging(3)).is_charging());
+    /// assert!(!DS4Status::with_battery_status(BatteryStatus::Full).is_charging());
+    /// assert!(!DS4Status::with_battery_status(BatteryStatus::NotCharging).is_charging());
+    /// ```
+    #[inline]
+    pub fn is_charging(&self) -> bool {
+        self.is_wired() && matches!(self.battery_status(), BatteryStatus::Charging(_))
+    }
+
+    /// Returns the battery charge level in 10% steps, in the range 0..=10.
+    ///
+    /// The level of [`BatteryStatus::Charging`] is returned as is and [`BatteryStatus::Full`] returns 10.
+    /// The error states carry no charge level and return 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4Status, BatteryStatus};
+    /// assert_eq!(DS4Status::with_battery_status(BatteryStatus::Charging(7)).battery_level(), 7);
+    /// assert_eq!(DS4Status::with_battery_status(BatteryStatus::Charging(20)).battery_level(), 10);
+    /// assert_eq!(DS4Status::with_battery_status(BatteryStatus::Full).battery_level(), 10);
+    /// assert_eq!(DS4Status::with_battery_status(BatteryStatus::Error).battery_level(), 0);
+    /// ```
+    #[inline]
+    pub fn battery_level(&self) -> u8 {
+        match self.battery_status() {
+            BatteryStatus::Charging(capacity) => capacity,
+            BatteryStatus::Full => 10,
+            BatteryStatus::Error | BatteryStatus::NotCharging => 0,
+        }
+    }
 }
 
 impl Default for DS4Status {
+    /// A wired controller with a full battery.
     fn default() -> Self {
-        DS4Status(DS4Status::CABLE_STATE)
+        DS4Status::with_battery_status(BatteryStatus::Full)
     }
 }
 
@@ -380,8 +1064,47 @@ impl From<DS4Status> for u16 {
     }
 }
 
+#[doc(hidden)]
+impl From<u16> for DS4Status {
+    #[inline]
+    fn from(status: u16) -> Self {
+        DS4Status(status)
+    }
+}
+
+/// Convert a thumb stick axis value in `-1.0..=1.0` to the DualShock4 `0..=255` range.
+///
+/// The mapping is `round((value + 1.0) * 127.5)` after clamping `value` to `-1.0..=1.0`,
+/// so `-1.0` maps to `0`, `0.0` to the `0x80` center and `1.0` to `255`.
+/// `NaN` maps to the center.
+///
+/// # Examples
+///
+/// ```rust
+/// # use vigem_client::axis_from_f32;
+/// assert_eq!(axis_from_f32(-1.0), 0);
+/// assert_eq!(axis_from_f32(0.0), 0x80);
+/// assert_eq!(axis_from_f32(1.0), 255);
+/// assert_eq!(axis_from_f32(2.0), 255);
+/// ```
+#[inline]
+pub fn axis_from_f32(value: f32) -> u8 {
+    if value.is_nan() {
+        return 0x80;
+    }
+    ((value.clamp(-1.0, 1.0) + 1.0) * 127.5).round() as u8
+}
+
 /// A builder for [`DS4Report`].
 ///
+/// Fields which are not set default to a neutral state: sticks centered at `0x80`,
+/// triggers at `0` and no buttons pressed.
+///
+/// ```rust
+/// # use vigem_client::{DS4ReportBuilder, DS4Report};
+/// assert_eq!(DS4ReportBuilder::new().build(), DS4Report::default());
+/// ```
+///
 /// # Examples
 ///
 /// ```rust
@@ -417,6 +1140,25 @@ impl DS4ReportBuilder {
         Self::default()
     }
 
+    /// Reset all fields to their defaults, leaving the builder identical to [`new`](Self::new).
+    ///
+    /// The builder lives on the stack and never allocates, this allows reusing a builder kept across frames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4ReportBuilder;
+    /// let mut builder = DS4ReportBuilder::new().thumb_lx(0x20);
+    /// let _report = builder.clone().build();
+    /// builder.reset();
+    /// assert_eq!(builder, DS4ReportBuilder::new());
+    /// ```
+    #[inline]
+    pub fn reset(&mut self) -> &mut Self {
+        *self = Self::default();
+        self
+    }
+
     /// Set the left thumb stick X axis.
     #[inline]
     pub fn thumb_lx(mut self, value: u8) -> Self {
@@ -445,6 +1187,30 @@ impl DS4ReportBuilder {
         self
     }
 
+    /// Set the left thumb stick X axis from a value in `-1.0..=1.0`, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_lx_f32(self, value: f32) -> Self {
+        self.thumb_lx(axis_from_f32(value))
+    }
+
+    /// Set the left thumb stick Y axis from a value in `-1.0..=1.0` where `-1.0` is up, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_ly_f32(self, value: f32) -> Self {
+        self.thumb_ly(axis_from_f32(value))
+    }
+
+    /// Set the right thumb stick X axis from a value in `-1.0..=1.0`, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_rx_f32(self, value: f32) -> Self {
+        self.thumb_rx(axis_from_f32(value))
+    }
+
+    /// Set the right thumb stick Y axis from a value in `-1.0..=1.0` where `-1.0` is up, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_ry_f32(self, value: f32) -> Self {
+        self.thumb_ry(axis_from_f32(value))
+    }
+
     /// Set the buttons.
     ///
     /// # Examples
@@ -487,6 +1253,34 @@ impl DS4ReportBuilder {
         self
     }
 
+    /// Fully press the left trigger, setting the digital L2 button and the analog value to `255`.
+    ///
+    /// Many games require both, see [`DS4Buttons::l2`].
+    /// Setting the buttons with [`buttons`](Self::buttons) afterwards replaces the digital bit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4ReportBuilder, DS4Buttons};
+    /// let report = DS4ReportBuilder::new().trigger_l_full().build();
+    /// assert_eq!(report.trigger_l(), 255);
+    /// assert_eq!(report.buttons(), DS4Buttons::new().l2(true));
+    /// ```
+    #[inline]
+    pub fn trigger_l_full(mut self) -> Self {
+        self.buttons |= DS4Buttons::TRIGGER_LEFT;
+        self.trigger_l(u8::MAX)
+    }
+
+    /// Fully press the right trigger, setting the digital R2 button and the analog value to `255`.
+    ///
+    /// See [`trigger_l_full`](Self::trigger_l_full).
+    #[inline]
+    pub fn trigger_r_full(mut self) -> Self {
+        self.buttons |= DS4Buttons::TRIGGER_RIGHT;
+        self.trigger_r(u8::MAX)
+    }
+
     /// Build the report.
     #[inline]
     pub fn build(self) -> DS4Report {
@@ -595,6 +1389,59 @@ impl DS4ReportExBuilder {
         Self::default()
     }
 
+    /// Reset all fields to their defaults, leaving the builder identical to [`new`](Self::new).
+    ///
+    /// The builder lives on the stack and never allocates, this allows reusing a builder kept across frames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4ReportExBuilder;
+    /// let mut builder = DS4ReportExBuilder::new().gyro_x(1900);
+    /// let _report = builder.clone().build();
+    /// builder.reset();
+    /// assert_eq!(builder, DS4ReportExBuilder::new());
+    /// ```
+    #[inline]
+    pub fn reset(&mut self) -> &mut Self {
+        *self = Self::default();
+        self
+    }
+
+    /// Create a builder with the sticks, buttons and triggers of a basic report.
+    ///
+    /// The extended fields are left at their defaults, ready to add touch or motion data.
+    /// The fields copied from the basic report are kept exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4ReportBuilder, DS4ReportExBuilder, DS4Buttons};
+    /// let basic = DS4ReportBuilder::new()
+    ///     .thumb_lx(0x20)
+    ///     .buttons(DS4Buttons::new().cross(true))
+    ///     .trigger_r(0xff)
+    ///     .build();
+    /// let report = DS4ReportExBuilder::from_report(&basic).gyro(10, 20, 30).build();
+    /// assert_eq!(report.thumb_lx(), basic.thumb_lx());
+    /// assert_eq!(report.buttons(), basic.buttons());
+    /// assert_eq!(report.trigger_r(), basic.trigger_r());
+    /// ```
+    #[inline]
+    pub fn from_report(report: &DS4Report) -> Self {
+        DS4ReportExBuilder {
+            thumb_lx: Some(report.thumb_lx),
+            thumb_ly: Some(report.thumb_ly),
+            thumb_rx: Some(report.thumb_rx),
+            thumb_ry: Some(report.thumb_ry),
+            buttons: DS4Buttons(report.buttons),
+            special: DS4SpecialButtons(report.special),
+            trigger_l: Some(report.trigger_l),
+            trigger_r: Some(report.trigger_r),
+            ..Self::default()
+        }
+    }
+
     /// Set the left thumb stick X axis.
     #[inline]
     pub fn thumb_lx(mut self, value: u8) -> Self {
@@ -623,6 +1470,30 @@ impl DS4ReportExBuilder {
         self
     }
 
+    /// Set the left thumb stick X axis from a value in `-1.0..=1.0`, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_lx_f32(self, value: f32) -> Self {
+        self.thumb_lx(axis_from_f32(value))
+    }
+
+    /// Set the left thumb stick Y axis from a value in `-1.0..=1.0` where `-1.0` is up, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_ly_f32(self, value: f32) -> Self {
+        self.thumb_ly(axis_from_f32(value))
+    }
+
+    /// Set the right thumb stick X axis from a value in `-1.0..=1.0`, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_rx_f32(self, value: f32) -> Self {
+        self.thumb_rx(axis_from_f32(value))
+    }
+
+    /// Set the right thumb stick Y axis from a value in `-1.0..=1.0` where `-1.0` is up, see [`axis_from_f32`] for the mapping.
+    #[inline]
+    pub fn thumb_ry_f32(self, value: f32) -> Self {
+        self.thumb_ry(axis_from_f32(value))
+    }
+
     /// Set the buttons.
     #[inline]
     pub fn buttons(mut self, value: DS4Buttons) -> Self {
@@ -651,6 +1522,34 @@ impl DS4ReportExBuilder {
         self
     }
 
+    /// Fully press the left trigger, setting the digital L2 button and the analog value to `255`.
+    ///
+    /// Many games require both, see [`DS4Buttons::l2`].
+    /// Setting the buttons with [`buttons`](Self::buttons) afterwards replaces the digital bit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4ReportExBuilder, DS4Buttons};
+    /// let report = DS4ReportExBuilder::new().trigger_l_full().build();
+    /// assert_eq!(report.trigger_l(), 255);
+    /// assert_eq!(report.buttons(), DS4Buttons::new().l2(true));
+    /// ```
+    #[inline]
+    pub fn trigger_l_full(mut self) -> Self {
+        self.buttons |= DS4Buttons::TRIGGER_LEFT;
+        self.trigger_l(u8::MAX)
+    }
+
+    /// Fully press the right trigger, setting the digital R2 button and the analog value to `255`.
+    ///
+    /// See [`trigger_l_full`](Self::trigger_l_full).
+    #[inline]
+    pub fn trigger_r_full(mut self) -> Self {
+        self.buttons |= DS4Buttons::TRIGGER_RIGHT;
+        self.trigger_r(u8::MAX)
+    }
+
     /// Set the timestamp.
     #[inline]
     pub fn timestamp(mut self, value: u16) -> Self {
@@ -707,6 +1606,33 @@ impl DS4ReportExBuilder {
         self
     }
 
+    /// Set the gyroscope axes at once.
+    ///
+    /// The values are the angular velocity around each axis as reported by the controller's IMU,
+    /// at a resolution of roughly 16 units per degree per second (±2000°/s full range):
+    ///
+    /// * `x`: pitch, tilting the controller forward or backward.
+    /// * `y`: yaw, turning the controller left or right.
+    /// * `z`: roll, rotating the controller around the axis pointing away from the player.
+    ///
+    /// The axes follow the right-hand rule with X pointing right, Y pointing up and Z pointing towards the player.
+    #[inline]
+    pub fn gyro(self, x: i16, y: i16, z: i16) -> Self {
+        self.gyro_x(x).gyro_y(y).gyro_z(z)
+    }
+
+    /// Set the accelerometer axes at once.
+    ///
+    /// The values are the acceleration along each axis as reported by the controller's IMU,
+    /// at a resolution of roughly 8192 units per g (±4g full range).
+    /// A controller lying flat at rest reports about `(0, 8192, 0)`.
+    ///
+    /// The axes use the same convention as [`gyro`](Self::gyro): X pointing right, Y pointing up and Z pointing towards the player.
+    #[inline]
+    pub fn accel(self, x: i16, y: i16, z: i16) -> Self {
+        self.accel_x(x).accel_y(y).accel_z(z)
+    }
+
     /// Set the status.
     #[inline]
     pub fn status(mut self, value: DS4Status) -> Self {
@@ -788,3 +1714,242 @@ impl DS4ReportExBuilder {
         }
     }
 }
+
+impl From<DS4Report> for DS4ReportEx {
+    /// Convert a basic report, see [`DS4ReportExBuilder::from_report`].
+    #[inline]
+    fn from(report: DS4Report) -> Self {
+        DS4ReportExBuilder::from_report(&report).build()
+    }
+}
+
+/// DualShock4 output report received through notifications.
+///
+/// Contains the rumble motor speeds and the lightbar color set by the game.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub struct DS4OutputReport {
+    pub small_motor: u8,
+    pub large_motor: u8,
+    pub lightbar_color: DS4LightbarColor,
+}
+
+impl DS4OutputReport {
+    /// Returns the small (right, high frequency) motor speed normalized to `0.0..=1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::DS4OutputReport;
+    /// let report = DS4OutputReport { small_motor: 255, large_motor: 51, ..Default::default() };
+    /// assert_eq!(report.small_motor_f32(), 1.0);
+    /// assert_eq!(report.large_motor_f32(), 0.2);
+    /// ```
+    #[inline]
+    pub fn small_motor_f32(&self) -> f32 {
+        self.small_motor as f32 / 255.0
+    }
+
+    /// Returns the large (left, low frequency) motor speed normalized to `0.0..=1.0`.
+    #[inline]
+    pub fn large_motor_f32(&self) -> f32 {
+        self.large_motor as f32 / 255.0
+    }
+
+    /// Encodes the rumble and lightbar as an output report for a physical DualShock4 connected over USB.
+    ///
+    /// The buffer is the USB HID output report `0x05` including the report id, ready to be written to the device.
+    /// The report enables the rumble, lightbar and flash fields, the flash durations are zero which stops any flashing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4OutputReport, DS4LightbarColor};
+    /// let report = DS4OutputReport { small_motor: 0x10, large_motor: 0x20, lightbar_color: DS4LightbarColor::new(0xFF, 0x00, 0x80) };
+    /// let buffer = report.to_hid_output();
+    /// assert_eq!(buffer[..11], [0x05, 0x07, 0x04, 0x00, 0x10, 0x20, 0xFF, 0x00, 0x80, 0x00, 0x00]);
+    /// ```
+    #[inline]
+    pub fn to_hid_output(&self) -> [u8; 32] {
+        let mut buffer = [0u8; 32];
+        buffer[..9].copy_from_slice(&[
+            0x05,
+            0x07, // enable rumble, lightbar and flash
+            0x04,
+            0x00,
+            self.small_motor,
+            self.large_motor,
+            self.lightbar_color.red,
+            self.lightbar_color.green,
+            self.lightbar_color.blue,
+        ]);
+        buffer
+    }
+}
+
+/// Complete DualShock4 output report as written by the game.
+///
+/// Unlike [`DS4OutputReport`] it preserves the full buffer, including the lightbar flash durations.
+/// The buffer is the USB HID output report `0x05` starting with the report id,
+/// the accessors decode the fields at their offsets in that report.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DS4OutputReportEx {
+    raw: [u8; 64],
+}
+
+impl fmt::Debug for DS4OutputReportEx {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DS4OutputReportEx")
+            .field("small_motor", &self.small_motor())
+            .field("large_motor", &self.large_motor())
+            .field("lightbar_color", &self.lightbar_color())
+            .field("flash_on", &self.flash_on())
+            .field("flash_off", &self.flash_off())
+            .finish()
+    }
+}
+
+impl DS4OutputReportEx {
+    /// Create an output report from its raw bytes.
+    #[inline]
+    pub const fn from_raw(raw: [u8; 64]) -> Self {
+        DS4OutputReportEx { raw }
+    }
+
+    /// Get the raw bytes of the output report.
+    #[inline]
+    pub fn raw(&self) -> &[u8; 64] {
+        &self.raw
+    }
+
+    /// Get the speed of the small (weak, right) rumble motor.
+    #[inline]
+    pub fn small_motor(&self) -> u8 {
+        self.raw[4]
+    }
+
+    /// Get the speed of the large (strong, left) rumble motor.
+    #[inline]
+    pub fn large_motor(&self) -> u8 {
+        self.raw[5]
+    }
+
+    /// Get the lightbar color.
+    #[inline]
+    pub fn lightbar_color(&self) -> DS4LightbarColor {
+        DS4LightbarColor::new(self.raw[6], self.raw[7], self.raw[8])
+    }
+
+    /// Get the duration the lightbar is on while flashing, in units of 10ms.
+    #[inline]
+    pub fn flash_on(&self) -> u8 {
+        self.raw[9]
+    }
+
+    /// Get the duration the lightbar is off while flashing, in units of 10ms.
+    #[inline]
+    pub fn flash_off(&self) -> u8 {
+        self.raw[10]
+    }
+
+    /// Get the decoded rumble and lightbar fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use vigem_client::{DS4OutputReportEx, DS4LightbarColor};
+    /// let mut raw = [0u8; 64];
+    /// raw[..11].copy_from_slice(&[0x05, 0x07, 0, 0, 0x10, 0x20, 0xFF, 0x00, 0x80, 25, 50]);
+    /// let report = DS4OutputReportEx::from_raw(raw).report();
+    /// assert_eq!(report.small_motor, 0x10);
+    /// assert_eq!(report.large_motor, 0x20);
+    /// assert_eq!(report.lightbar_color, DS4LightbarColor::new(0xFF, 0x00, 0x80));
+    /// ```
+    #[inline]
+    pub fn report(&self) -> DS4OutputReport {
+        DS4OutputReport {
+            small_motor: self.small_motor(),
+            large_motor: self.large_motor(),
+            lightbar_color: self.lightbar_color(),
+        }
+    }
+}
+
+/// DualShock4 lightbar color.
+///
+/// Converts to and from a packed `0x00RRGGBB` integer.
+///
+/// # Examples
+///
+/// ```rust
+/// # use vigem_client::DS4LightbarColor;
+/// let color = DS4LightbarColor { red: 0xFF, green: 0x80, blue: 0x00 };
+/// assert_eq!(u32::from(color), 0xFF8000);
+/// assert_eq!(DS4LightbarColor::from(0xFF8000), color);
+/// assert_eq!(DS4LightbarColor::from((0xFF, 0x80, 0x00)), color);
+/// assert_eq!(u32::from(DS4LightbarColor::BLUE), 0x0000FF);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub struct DS4LightbarColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl DS4LightbarColor {
+    /// Lightbar turned off.
+    pub const OFF: DS4LightbarColor = DS4LightbarColor::new(0x00, 0x00, 0x00);
+    /// Red.
+    pub const RED: DS4LightbarColor = DS4LightbarColor::new(0xFF, 0x00, 0x00);
+    /// Green.
+    pub const GREEN: DS4LightbarColor = DS4LightbarColor::new(0x00, 0xFF, 0x00);
+    /// Blue.
+    pub const BLUE: DS4LightbarColor = DS4LightbarColor::new(0x00, 0x00, 0xFF);
+    /// Yellow.
+    pub const YELLOW: DS4LightbarColor = DS4LightbarColor::new(0xFF, 0xFF, 0x00);
+    /// Cyan.
+    pub const CYAN: DS4LightbarColor = DS4LightbarColor::new(0x00, 0xFF, 0xFF);
+    /// Magenta.
+    pub const MAGENTA: DS4LightbarColor = DS4LightbarColor::new(0xFF, 0x00, 0xFF);
+    /// White.
+    pub const WHITE: DS4LightbarColor = DS4LightbarColor::new(0xFF, 0xFF, 0xFF);
+
+    /// Create a new color from its components.
+    #[inline]
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        DS4LightbarColor { red, green, blue }
+    }
+}
+
+impl From<(u8, u8, u8)> for DS4LightbarColor {
+    #[inline]
+    fn from((red, green, blue): (u8, u8, u8)) -> Self {
+        DS4LightbarColor { red, green, blue }
+    }
+}
+
+impl From<DS4LightbarColor> for (u8, u8, u8) {
+    #[inline]
+    fn from(color: DS4LightbarColor) -> Self {
+        (color.red, color.green, color.blue)
+    }
+}
+
+impl From<u32> for DS4LightbarColor {
+    #[inline]
+    fn from(rgb: u32) -> Self {
+        DS4LightbarColor {
+            red: (rgb >> 16) as u8,
+            green: (rgb >> 8) as u8,
+            blue: rgb as u8,
+        }
+    }
+}
+
+impl From<DS4LightbarColor> for u32 {
+    #[inline]
+    fn from(color: DS4LightbarColor) -> Self {
+        (color.red as u32) << 16 | (color.green as u32) << 8 | color.blue as u32
+    }
+}
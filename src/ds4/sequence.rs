@@ -0,0 +1,123 @@
+//! Recording and replaying of DualShock4 input.
+
+use super::DS4ReportEx;
+use crate::{Client, DualShock4Wired, Error};
+
+use std::borrow::Borrow;
+use std::{thread, time};
+
+/// A single timestamped frame of an [`InputSequence`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputFrame {
+    /// Time since the first frame of the sequence.
+    pub offset: time::Duration,
+    /// The report submitted for this frame.
+    pub report: DS4ReportEx,
+}
+
+/// A recorded sequence of timestamped reports which can be replayed against a target.
+///
+/// Frames are recorded with [`InputSequence::push`] in chronological order,
+/// their timing is stored relative to the first frame so a sequence can be saved and replayed later.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use vigem_client::{Client, DS4ReportExBuilder, DS4Buttons, DualShock4Wired, InputSequence, TargetId};
+/// use std::time::{Duration, Instant};
+///
+/// let mut sequence = InputSequence::new();
+/// let start = Instant::now();
+/// sequence.push(start, DS4ReportExBuilder::new().buttons(DS4Buttons::new().cross(true)).build());
+/// sequence.push(start + Duration::from_millis(100), DS4ReportExBuilder::new().build());
+///
+/// let mut target = DualShock4Wired::new(Client::connect().unwrap(), TargetId::DUALSHOCK4_WIRED);
+/// target.plugin().unwrap();
+/// target.wait_ready().unwrap();
+/// sequence.play(&mut target).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputSequence {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    start: Option<time::Instant>,
+    frames: Vec<InputFrame>,
+}
+
+impl InputSequence {
+    /// Create an empty sequence.
+    #[inline]
+    pub fn new() -> Self {
+        InputSequence::default()
+    }
+
+    /// Record a report captured at `instant`.
+    ///
+    /// The first pushed frame defines the start of the sequence.
+    /// Frames captured before the start are clamped to it.
+    pub fn push(&mut self, instant: time::Instant, report: DS4ReportEx) {
+        let offset = match self.start {
+            Some(start) => instant.saturating_duration_since(start),
+            None if self.frames.is_empty() => {
+                self.start = Some(instant);
+                time::Duration::ZERO
+            },
+            // A deserialized sequence continues after its last frame
+            None => {
+                let last = self.duration();
+                self.start = instant.checked_sub(last);
+                last
+            },
+        };
+        self.frames.push(InputFrame { offset, report });
+    }
+
+    /// Get the recorded frames.
+    #[inline]
+    pub fn frames(&self) -> &[InputFrame] {
+        &self.frames
+    }
+
+    /// Get the number of recorded frames.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns if no frames were recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get the offset of the last frame.
+    #[inline]
+    pub fn duration(&self) -> time::Duration {
+        self.frames.last().map_or(time::Duration::ZERO, |frame| frame.offset)
+    }
+
+    /// Remove all frames, the next pushed frame starts a new sequence.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.frames.clear();
+    }
+
+    /// Replay the sequence against a target, honoring the original timing.
+    ///
+    /// Blocks the current thread, sleeping between frames.
+    /// Frames which are late because a previous submission blocked are submitted immediately.
+    /// Stops at the first error returned by [`DualShock4Wired::update_ex`].
+    pub fn play<CL: Borrow<Client>>(&self, target: &mut DualShock4Wired<CL>) -> Result<(), Error> {
+        let start = time::Instant::now();
+        for frame in &self.frames {
+            let elapsed = start.elapsed();
+            if frame.offset > elapsed {
+                thread::sleep(frame.offset - elapsed);
+            }
+            target.update_ex(&frame.report)?;
+        }
+        Ok(())
+    }
+}
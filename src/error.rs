@@ -1,4 +1,5 @@
-use std::{error, fmt};
+use std::{error, fmt, io, ptr};
+use winapi::um::winbase::*;
 
 /// ViGEm client errors.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -6,20 +7,44 @@ use std::{error, fmt};
 pub enum Error {
 	/// There was an unexpected windows error.
 	///
+	/// `code` is the system error code and `operation` the request to the driver which failed.
+	/// Match with `Error::WinError { code, .. }` when only the code matters.
+	///
 	/// See [System Error Codes](https://docs.microsoft.com/en-us/windows/win32/debug/system-error-codes) for more information.
-	WinError(u32),
+	WinError {
+		/// The system error code.
+		code: u32,
+		/// The operation which failed.
+		operation: Operation,
+	},
 	/// The ViGEmBus Driver is not installed.
 	///
 	/// It can be installed from the [ViGEmBus](https://github.com/ViGEm/ViGEmBus) repository.
+	///
+	/// Returned by [`Client::connect`](crate::Client::connect) when no bus device exists,
+	/// or when the bus device disappears while opening it (`ERROR_FILE_NOT_FOUND`), eg. while the driver is being uninstalled.
+	/// Applications can show an installation hint for this error without interpreting Windows error codes.
+	#[doc(alias = "DriverNotInstalled")]
 	BusNotFound,
 	/// ViGEmBus was found, but accessing it returned an error.
 	BusAccessFailed(u32),
+	/// Duplicating the ViGEmBus service handle failed with the given system error code.
+	///
+	/// Returned by [`Client::try_clone`](crate::Client::try_clone) when `DuplicateHandle` fails,
+	/// eg. with `ERROR_NOT_ENOUGH_MEMORY` or `ERROR_NO_SYSTEM_RESOURCES` when the process is out of handles.
+	DuplicateHandleFailed(u32),
 	/// ViGEmBus was found, but it did not accept this client's version.
 	BusVersionMismatch,
 	/// There was no more room to allocate new targets.
 	NoFreeSlot,
+	/// The requested serial number is already used by another target.
+	SerialInUse,
 	// InvalidClient,
 	// InvalidTarget,
+	/// A target with the same vendor and product ids is already plugged in by this client.
+	///
+	/// Only returned when enabled with [`Client::reject_duplicate_ids`](crate::Client::reject_duplicate_ids).
+	DuplicateTargetId,
 	/// The target is already connected.
 	///
 	/// It is an error to try to plugin an already connected target.
@@ -37,30 +62,171 @@ pub enum Error {
 	UserIndexOutOfRange,
 	/// The operation was aborted.
 	OperationAborted,
+	/// The operation did not complete before the timeout elapsed.
+	Timeout,
+	/// The update was submitted before the minimum interval elapsed and was skipped.
+	///
+	/// See [`DualShock4Wired::set_min_interval`](crate::DualShock4Wired::set_min_interval).
+	TooFast,
+	/// The ViGEmBus driver was restarted.
+	///
+	/// All targets of the client are gone, reconnect the client with [`Client::reconnect`](crate::Client::reconnect) and plug the targets in again.
+	DriverRestarted,
+	/// The operation is not supported by the installed ViGEmBus driver.
+	Unsupported,
+	/// A notification object holds the buffer of another target type.
+	///
+	/// This indicates a bug in this crate rather than a driver error.
+	InternalVariantMismatch,
+}
+
+/// The operation which failed with an [`Error::WinError`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+	/// Plugging in a target.
+	Plugin,
+	/// Unplugging a target.
+	Unplug,
+	/// Waiting for a target to become ready.
+	WaitReady,
+	/// Submitting a report.
+	Submit,
+	/// Requesting or waiting for a notification.
+	Notify,
+	/// Any other request, eg. locating the bus or querying the XInput user index.
+	Other,
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Operation::Plugin => "plugin",
+			Operation::Unplug => "unplug",
+			Operation::WaitReady => "wait ready",
+			Operation::Submit => "submit",
+			Operation::Notify => "notify",
+			Operation::Other => "other",
+		})
+	}
+}
+
+impl Error {
+	#[inline]
+	pub(crate) fn win(code: u32, operation: Operation) -> Error {
+		Error::WinError { code, operation }
+	}
+
+	/// Returns the system error code if this error wraps one.
+	#[inline]
+	pub fn win_error_code(&self) -> Option<u32> {
+		match *self {
+			Error::WinError { code, .. } => Some(code),
+			Error::BusAccessFailed(code) | Error::DuplicateHandleFailed(code) => Some(code),
+			_ => None,
+		}
+	}
+
+	/// Returns the operation which failed with a windows error.
+	#[inline]
+	pub fn operation(&self) -> Option<Operation> {
+		match *self {
+			Error::WinError { operation, .. } => Some(operation),
+			_ => None,
+		}
+	}
 }
 
 impl From<u32> for Error {
+	/// Wraps a system error code with [`Operation::Other`].
 	#[inline]
 	fn from(error: u32) -> Error {
-		Error::WinError(error)
+		Error::win(error, Operation::Other)
 	}
 }
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
-			Error::WinError(err) => write!(f, "win error: {}", err),
+			Error::WinError { code, operation: Operation::Other } => write!(f, "win error: {}", WinErrorMessage(code)),
+			Error::WinError { code, operation } => write!(f, "win error during {}: {}", operation, WinErrorMessage(code)),
 			Error::BusNotFound => f.write_str("bus not found"),
-			Error::BusAccessFailed(err) => write!(f, "bus access failed: {}", err),
+			Error::BusAccessFailed(err) => write!(f, "bus access failed: {}", WinErrorMessage(err)),
+			Error::DuplicateHandleFailed(err) => write!(f, "duplicate handle failed: {}", WinErrorMessage(err)),
 			Error::BusVersionMismatch => f.write_str("bus version mismatch"),
 			Error::NoFreeSlot => f.write_str("no free slot"),
+			Error::SerialInUse => f.write_str("serial in use"),
+			Error::DuplicateTargetId => f.write_str("duplicate target id"),
 			Error::AlreadyConnected => f.write_str("already connected"),
 			Error::NotPluggedIn => f.write_str("not plugged in"),
 			Error::TargetNotReady => f.write_str("target not ready"),
 			Error::UserIndexOutOfRange => f.write_str("user index out of range"),
 			Error::OperationAborted => f.write_str("operation aborted"),
+			Error::Timeout => f.write_str("timeout"),
+			Error::TooFast => f.write_str("too fast"),
+			Error::DriverRestarted => f.write_str("driver restarted"),
+			Error::Unsupported => f.write_str("unsupported"),
+			Error::InternalVariantMismatch => f.write_str("internal variant mismatch"),
 		}
 	}
 }
 
+// None of the variants wrap another error so there is no `source`.
 impl error::Error for Error {}
+
+/// Converts to an [`io::Error`] for use in `io::Result` contexts.
+///
+/// Errors carrying a system error code become raw OS errors, losing the failed [`Operation`].
+/// The other variants map to the closest [`ErrorKind`](io::ErrorKind) and keep the original error as the inner error.
+///
+/// ```
+/// # use vigem_client::Error;
+/// use std::io;
+///
+/// let err = io::Error::from(Error::NotPluggedIn);
+/// assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+/// assert_eq!(err.into_inner().unwrap().downcast_ref::<Error>(), Some(&Error::NotPluggedIn));
+/// ```
+impl From<Error> for io::Error {
+	fn from(error: Error) -> io::Error {
+		let kind = match error {
+			Error::WinError { code, .. } | Error::BusAccessFailed(code) | Error::DuplicateHandleFailed(code) => {
+				return io::Error::from_raw_os_error(code as i32);
+			},
+			Error::BusNotFound => io::ErrorKind::NotFound,
+			Error::BusVersionMismatch | Error::Unsupported => io::ErrorKind::Unsupported,
+			Error::SerialInUse | Error::DuplicateTargetId | Error::AlreadyConnected => io::ErrorKind::AlreadyExists,
+			Error::NotPluggedIn => io::ErrorKind::NotConnected,
+			Error::OperationAborted => io::ErrorKind::ConnectionAborted,
+			Error::DriverRestarted => io::ErrorKind::ConnectionReset,
+			Error::Timeout => io::ErrorKind::TimedOut,
+			Error::TooFast => io::ErrorKind::WouldBlock,
+			Error::NoFreeSlot | Error::TargetNotReady | Error::UserIndexOutOfRange | Error::InternalVariantMismatch => io::ErrorKind::Other,
+		};
+		io::Error::new(kind, error)
+	}
+}
+
+/// Formats a system error code with its message from the system message table.
+struct WinErrorMessage(u32);
+
+impl fmt::Display for WinErrorMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut buffer = [0u16; 512];
+		let len = unsafe {
+			FormatMessageW(
+				FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+				ptr::null(),
+				self.0,
+				0,
+				buffer.as_mut_ptr(),
+				buffer.len() as u32,
+				ptr::null_mut())
+		};
+		if len == 0 {
+			return write!(f, "{}", self.0);
+		}
+		let message = String::from_utf16_lossy(&buffer[..len as usize]);
+		write!(f, "{} ({})", message.trim_end(), self.0)
+	}
+}
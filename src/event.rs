@@ -1,7 +1,9 @@
-use std::{fmt, ptr};
+use std::{fmt, ptr, time};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use winapi::um::handleapi::*;
 use winapi::um::synchapi::*;
 use winapi::shared::ntdef::HANDLE;
+use winapi::um::winbase::INFINITE;
 
 #[repr(transparent)]
 pub struct Event {
@@ -40,3 +42,98 @@ impl Drop for Event {
 		unsafe { CloseHandle(self.handle) };
 	}
 }
+
+/// Reset mode of the event a target waits on for its requests.
+///
+/// The default [`AutoReset`](EventMode::AutoReset) matches the official ViGEmClient library:
+/// every completed request signals the event once and the wait which observes it also clears it,
+/// so a stale signal can never leak into the next request.
+///
+/// [`ManualReset`](EventMode::ManualReset) keeps the event signaled until the next request is started.
+/// This helps when more than one wait may observe the same completion, eg. when the event is also waited on
+/// from outside the crate or when several completions are coalesced before anyone waits:
+/// with auto-reset only the first waiter wakes up and the others block until the next completion.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EventMode {
+	/// The event is reset by the wait which observes it.
+	#[default]
+	AutoReset,
+	/// The event stays signaled until the next request is started.
+	ManualReset,
+}
+
+/// Event shared by all the targets of a client, see `Client::shared_event_mode`.
+///
+/// Only one request may use the event at a time, the lock is held for the duration of the request.
+#[derive(Debug)]
+pub(crate) struct SharedEvent {
+	event: Event,
+	lock: Mutex<()>,
+}
+impl SharedEvent {
+	#[inline]
+	pub(crate) fn new() -> SharedEvent {
+		SharedEvent { event: Event::new(false, false), lock: Mutex::new(()) }
+	}
+}
+
+/// Event used by a target for its requests.
+pub(crate) enum TargetEvent {
+	Owned(Event),
+	Shared(Arc<SharedEvent>),
+}
+impl TargetEvent {
+	#[inline]
+	pub(crate) fn new(shared: Option<&Arc<SharedEvent>>, mode: EventMode) -> TargetEvent {
+		match shared {
+			Some(shared) => TargetEvent::Shared(shared.clone()),
+			None => TargetEvent::Owned(Event::new(mode == EventMode::ManualReset, false)),
+		}
+	}
+	/// Returns the event handle without taking the lock.
+	#[inline]
+	pub(crate) fn handle(&self) -> HANDLE {
+		match self {
+			TargetEvent::Owned(event) => event.handle,
+			TargetEvent::Shared(shared) => shared.event.handle,
+		}
+	}
+	#[inline]
+	pub(crate) fn is_shared(&self) -> bool {
+		matches!(self, TargetEvent::Shared(_))
+	}
+	/// Locks the event for a request, the guard must be held until the request completes.
+	#[inline]
+	pub(crate) fn lock(&self) -> EventGuard<'_> {
+		match self {
+			TargetEvent::Owned(event) => EventGuard { handle: event.handle, _lock: None },
+			TargetEvent::Shared(shared) => {
+				let lock = shared.lock.lock().unwrap_or_else(PoisonError::into_inner);
+				EventGuard { handle: shared.event.handle, _lock: Some(lock) }
+			},
+		}
+	}
+}
+
+impl fmt::Debug for TargetEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Event")
+			.field("handle", &self.handle())
+			.field("shared", &self.is_shared())
+			.finish()
+	}
+}
+
+pub(crate) struct EventGuard<'a> {
+	pub(crate) handle: HANDLE,
+	_lock: Option<MutexGuard<'a, ()>>,
+}
+
+/// Converts a timeout to milliseconds for the wait functions.
+///
+/// Rounds up to whole milliseconds and clamps to `INFINITE`.
+#[inline]
+pub fn timeout_ms(timeout: time::Duration) -> u32 {
+	let ms = timeout.as_nanos().div_ceil(1_000_000);
+	if ms >= INFINITE as u128 { INFINITE } else { ms as u32 }
+}
@@ -106,18 +106,31 @@ let _ = target.update(&gamepad);
 The DualShock4Wired target is under development.
 */
 
+#[macro_use]
+mod trace;
 mod bus;
 mod event;
 mod error;
 mod client;
+mod notification;
 mod x360;
 mod ds4;
+mod pad;
+mod rate;
+mod metrics;
+#[cfg(feature = "sdl2")]
+mod sdl2;
 
 use self::event::*;
-pub use self::error::Error;
+pub use self::error::{Error, Operation};
+pub use self::event::EventMode;
 pub use self::client::*;
+pub use self::notification::{NotificationStop, SpawnOptions};
 pub use self::x360::*;
 pub use self::ds4::*;
+pub use self::pad::VirtualPad;
+pub use self::rate::RateLimiter;
+pub use self::metrics::TargetMetrics;
 
 /// Vendor and product ids.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
@@ -131,4 +144,48 @@ impl TargetId {
 	pub const XBOX360_WIRED: TargetId = TargetId { vendor: 0x045E, product: 0x028E };
 	/// Default vender and product ids for a wired DualShock4 target.
 	pub const DUALSHOCK4_WIRED: TargetId = TargetId { vendor: 0x054C, product: 0x05C4 };
+	/// Vendor and product ids of the first DualShock4 revision, model CUH-ZCT1.
+	///
+	/// Same as [`DUALSHOCK4_WIRED`](Self::DUALSHOCK4_WIRED).
+	pub const DUALSHOCK4_V1: TargetId = TargetId { vendor: 0x054C, product: 0x05C4 };
+	/// Vendor and product ids of the second DualShock4 revision, model CUH-ZCT2, with the lightbar visible through the touchpad.
+	pub const DUALSHOCK4_V2: TargetId = TargetId { vendor: 0x054C, product: 0x09CC };
+	/// Vendor and product ids of the Sony DualShock4 USB wireless adaptor, model CUH-ZWA1.
+	pub const DUALSHOCK4_WIRELESS_ADAPTOR: TargetId = TargetId { vendor: 0x054C, product: 0x0BA0 };
+
+	/// Creates a target id from raw vendor and product ids.
+	///
+	/// No validation is done, the driver rejects combinations it does not accept when the target is plugged in.
+	///
+	/// ```
+	/// let id = vigem_client::TargetId::new(0x054C, 0x09CC);
+	/// assert_eq!(id.vendor(), 0x054C);
+	/// assert_eq!(id.product(), 0x09CC);
+	/// ```
+	#[inline]
+	pub const fn new(vendor: u16, product: u16) -> TargetId {
+		TargetId { vendor, product }
+	}
+	/// Returns the vendor id.
+	#[inline]
+	pub const fn vendor(&self) -> u16 {
+		self.vendor
+	}
+	/// Returns the product id.
+	#[inline]
+	pub const fn product(&self) -> u16 {
+		self.product
+	}
+}
+
+/// Snapshot of the identity of a target.
+///
+/// A cheap copy of the id and serial number of a target at the time it was taken,
+/// to be passed around for logging and correlation without access to the live target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TargetHandle {
+	/// The vendor and product ids of the target.
+	pub id: TargetId,
+	/// The serial number of the target, `None` if it was not plugged in.
+	pub serial_no: Option<u32>,
 }
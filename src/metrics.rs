@@ -0,0 +1,33 @@
+use std::time;
+use crate::*;
+
+/// Counters of the reports submitted by a target.
+///
+/// Only updated while tracking is enabled with [`DualShock4Wired::track_metrics`].
+/// Updates rejected before they are submitted, eg. with [`Error::NotPluggedIn`] or [`Error::TooFast`], are not counted.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TargetMetrics {
+	/// Number of reports the driver accepted.
+	pub updates: u64,
+	/// Number of reports the driver rejected.
+	pub failed_updates: u64,
+	/// The error of the last rejected report.
+	pub last_error: Option<Error>,
+	/// Cumulative time spent waiting for the driver to complete the submit requests.
+	///
+	/// Reports submitted with [`DualShock4Wired::update_batch`] are counted but not timed.
+	pub ioctl_time: time::Duration,
+}
+
+impl TargetMetrics {
+	#[inline]
+	pub(crate) fn record(&mut self, result: &Result<(), Error>) {
+		match result {
+			Ok(()) => self.updates += 1,
+			Err(err) => {
+				self.failed_updates += 1;
+				self.last_error = Some(*err);
+			},
+		}
+	}
+}
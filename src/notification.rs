@@ -0,0 +1,147 @@
+use std::{fmt, panic, thread};
+use std::sync::{atomic, Arc, Mutex, MutexGuard, PoisonError};
+use winapi::um::minwinbase::OVERLAPPED;
+use winapi::shared::ntdef::HANDLE;
+use crate::*;
+
+pub(crate) struct PendingRequest {
+	device: HANDLE,
+	overlapped: *mut OVERLAPPED,
+}
+
+#[derive(Default)]
+pub(crate) struct StopState {
+	pub(crate) stopped: atomic::AtomicBool,
+	pending: Mutex<Option<PendingRequest>>,
+}
+impl StopState {
+	#[inline]
+	pub(crate) fn lock(&self) -> MutexGuard<'_, Option<PendingRequest>> {
+		self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+	/// Registers the pinned request which may be cancelled by `stop`.
+	///
+	/// The returned guard must be dropped before the registered request is dropped,
+	/// it unregisters the request even when the thread unwinds.
+	#[inline]
+	pub(crate) fn register(&self, device: HANDLE, overlapped: *mut OVERLAPPED) -> Registration<'_> {
+		*self.lock() = Some(PendingRequest { device, overlapped });
+		Registration { state: self }
+	}
+}
+
+pub(crate) struct Registration<'a> {
+	state: &'a StopState,
+}
+impl<'a> Drop for Registration<'a> {
+	#[inline]
+	fn drop(&mut self) {
+		*self.state.lock() = None;
+	}
+}
+// Safety: the pending request is only accessed while holding the lock and cleared before the request is dropped
+unsafe impl Sync for StopState {}
+unsafe impl Send for StopState {}
+
+/// Options for a notification thread.
+///
+/// Used with [`DSRequestNotification::spawn_thread_with_options`] and [`XRequestNotification::spawn_thread_with_options`].
+///
+/// By default a panic in the callback unwinds the notification thread, which ends it.
+/// No more notifications are delivered and the panic is only observed when the thread is joined.
+/// With [`catch_panics`](Self::catch_panics) the panic is caught instead and the thread continues with the next notification.
+/// In both cases the panic is reported by the panic hook, which by default prints the message and the thread name to stderr.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SpawnOptions {
+	catch_panics: bool,
+	name: Option<String>,
+}
+
+impl SpawnOptions {
+	/// Creates the default options.
+	#[inline]
+	pub fn new() -> SpawnOptions {
+		SpawnOptions::default()
+	}
+
+	/// Catches panics in the callback and continues with the next notification.
+	///
+	/// Any state captured by the callback is used again after a panic, it must be able to cope with being left half updated.
+	#[inline]
+	pub fn catch_panics(mut self, catch_panics: bool) -> SpawnOptions {
+		self.catch_panics = catch_panics;
+		self
+	}
+
+	/// Names the thread.
+	///
+	/// The name is included in panic messages and shown by debuggers.
+	#[inline]
+	pub fn name<S: Into<String>>(mut self, name: S) -> SpawnOptions {
+		self.name = Some(name.into());
+		self
+	}
+
+	#[inline]
+	pub(crate) fn spawn<T: Send + 'static, F: FnOnce() -> T + Send + 'static>(&self, f: F) -> thread::JoinHandle<T> {
+		let mut builder = thread::Builder::new();
+		if let Some(name) = &self.name {
+			builder = builder.name(name.clone());
+		}
+		builder.spawn(f).expect("failed to spawn thread")
+	}
+
+	// Invokes the callback, catching a panic if requested.
+	#[inline]
+	pub(crate) fn invoke<F: FnOnce()>(&self, f: F) {
+		if self.catch_panics {
+			let _ = panic::catch_unwind(panic::AssertUnwindSafe(f));
+		}
+		else {
+			f();
+		}
+	}
+}
+
+/// Handle to stop a notification thread.
+///
+/// Returned by [`DSRequestNotification::spawn_thread`] and [`XRequestNotification::spawn_thread`].
+#[derive(Clone)]
+pub struct NotificationStop {
+	pub(crate) state: Arc<StopState>,
+}
+
+impl NotificationStop {
+	#[inline]
+	pub(crate) fn new() -> NotificationStop {
+		NotificationStop { state: Arc::new(StopState::default()) }
+	}
+
+	/// Stops the notification thread.
+	///
+	/// Any pending notification request is cancelled so the thread returns promptly.
+	/// Calling this method more than once, or after the thread has already stopped, has no effect.
+	#[inline(never)]
+	pub fn stop(&self) {
+		self.state.stopped.store(true, atomic::Ordering::SeqCst);
+		if let Some(pending) = &*self.state.lock() {
+			unsafe {
+				let _ = bus::RequestNotification::cancel_async(pending.device, pending.overlapped);
+			}
+		}
+	}
+
+	/// Returns if the notification thread was asked to stop.
+	#[inline]
+	pub fn is_stopped(&self) -> bool {
+		self.state.stopped.load(atomic::Ordering::SeqCst)
+	}
+}
+
+impl fmt::Debug for NotificationStop {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("NotificationStop")
+			.field("stopped", &self.is_stopped())
+			.finish()
+	}
+}
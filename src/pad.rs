@@ -0,0 +1,90 @@
+use std::borrow::Borrow;
+use crate::*;
+
+/// Common interface of the virtual controllers.
+///
+/// Allows writing code once for both [`Xbox360Wired`] and [`DualShock4Wired`] targets.
+/// The concrete targets implement more methods for their device-specific features.
+///
+/// ```no_run
+/// use vigem_client::{Client, Error, TargetId, VirtualPad, XGamepad, Xbox360Wired};
+///
+/// fn attach<P: VirtualPad>(pad: &mut P, report: &P::Report) -> Result<(), Error> {
+///     pad.plugin()?;
+///     pad.wait_ready()?;
+///     pad.update(report)
+/// }
+///
+/// let client = Client::connect().unwrap();
+/// let mut target = Xbox360Wired::new(client, TargetId::XBOX360_WIRED);
+/// attach(&mut target, &XGamepad::default()).unwrap();
+/// ```
+pub trait VirtualPad {
+	/// The input report used to update the controller.
+	type Report;
+
+	/// Returns if the controller is plugged in.
+	fn is_attached(&self) -> bool;
+
+	/// Plugs the controller in.
+	fn plugin(&mut self) -> Result<(), Error>;
+
+	/// Unplugs the controller.
+	fn unplug(&mut self) -> Result<(), Error>;
+
+	/// Waits until the virtual controller is ready.
+	fn wait_ready(&mut self) -> Result<(), Error>;
+
+	/// Updates the virtual controller state.
+	fn update(&mut self, report: &Self::Report) -> Result<(), Error>;
+}
+
+impl<CL: Borrow<Client>> VirtualPad for Xbox360Wired<CL> {
+	type Report = XGamepad;
+
+	#[inline]
+	fn is_attached(&self) -> bool {
+		Xbox360Wired::is_attached(self)
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		Xbox360Wired::plugin(self)
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		Xbox360Wired::unplug(self)
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		Xbox360Wired::wait_ready(self)
+	}
+	#[inline]
+	fn update(&mut self, report: &XGamepad) -> Result<(), Error> {
+		Xbox360Wired::update(self, report)
+	}
+}
+
+impl<CL: Borrow<Client>> VirtualPad for DualShock4Wired<CL> {
+	type Report = DS4Report;
+
+	#[inline]
+	fn is_attached(&self) -> bool {
+		DualShock4Wired::is_attached(self)
+	}
+	#[inline]
+	fn plugin(&mut self) -> Result<(), Error> {
+		DualShock4Wired::plugin(self)
+	}
+	#[inline]
+	fn unplug(&mut self) -> Result<(), Error> {
+		DualShock4Wired::unplug(self)
+	}
+	#[inline]
+	fn wait_ready(&mut self) -> Result<(), Error> {
+		DualShock4Wired::wait_ready(self)
+	}
+	#[inline]
+	fn update(&mut self, report: &DS4Report) -> Result<(), Error> {
+		DualShock4Wired::update(self, report)
+	}
+}
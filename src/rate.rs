@@ -0,0 +1,69 @@
+use std::{thread, time};
+
+/// Paces submissions to a minimum interval.
+///
+/// Some games misbehave when receiving updates faster than a real controller sends them,
+/// a wired DualShock4 reports at about 250Hz, see [`DualShock4Wired::REPORT_INTERVAL`](crate::DualShock4Wired::REPORT_INTERVAL).
+///
+/// ```
+/// use std::time::Duration;
+/// let mut limiter = vigem_client::RateLimiter::new(Duration::from_secs(60));
+/// assert!(limiter.try_acquire());
+/// assert!(!limiter.try_acquire());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct RateLimiter {
+	interval: time::Duration,
+	last: Option<time::Instant>,
+}
+
+impl RateLimiter {
+	/// Creates a rate limiter allowing one submission per `interval`.
+	#[inline]
+	pub fn new(interval: time::Duration) -> RateLimiter {
+		RateLimiter { interval, last: None }
+	}
+
+	/// Returns the minimum interval between submissions.
+	#[inline]
+	pub fn interval(&self) -> time::Duration {
+		self.interval
+	}
+
+	/// Returns how long until the next submission is allowed, zero if it is allowed now.
+	#[inline]
+	pub fn remaining(&self) -> time::Duration {
+		match self.last {
+			Some(last) => self.interval.saturating_sub(last.elapsed()),
+			None => time::Duration::ZERO,
+		}
+	}
+
+	/// Records a submission if the interval has elapsed since the previous one.
+	///
+	/// Returns false without recording anything if called too soon.
+	#[inline]
+	pub fn try_acquire(&mut self) -> bool {
+		if self.remaining() > time::Duration::ZERO {
+			return false;
+		}
+		self.last = Some(time::Instant::now());
+		true
+	}
+
+	/// Sleeps until the next submission is allowed and records it.
+	#[inline]
+	pub fn wait(&mut self) {
+		let remaining = self.remaining();
+		if remaining > time::Duration::ZERO {
+			thread::sleep(remaining);
+		}
+		self.last = Some(time::Instant::now());
+	}
+
+	/// Forgets the previous submission, the next one is allowed immediately.
+	#[inline]
+	pub fn reset(&mut self) {
+		self.last = None;
+	}
+}
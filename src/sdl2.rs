@@ -0,0 +1,181 @@
+//! Conversion from [`sdl2`](::sdl2) game controller states.
+
+use ::sdl2::controller::{Axis, Button, GameController};
+use crate::*;
+
+// Maps a signed SDL axis to the DualShock4 range, 0 maps to the 0x80 center.
+#[inline]
+fn stick_to_u8(value: i16) -> u8 {
+	((value as i32 + 0x8000) >> 8) as u8
+}
+
+// Maps an SDL trigger axis in the range 0..=32767 to the 0..=255 range.
+#[inline]
+fn trigger_to_u8(value: i16) -> u8 {
+	(value.max(0) as i32 * 255 / i16::MAX as i32) as u8
+}
+
+// SDL reports down as positive while XInput reports up as positive.
+#[inline]
+fn invert_axis(value: i16) -> i16 {
+	value.checked_neg().unwrap_or(i16::MAX)
+}
+
+impl DS4ReportEx {
+	/// Creates a report from the polled state of an SDL [`GameController`].
+	///
+	/// Stick axes are mapped from SDL's signed `i16` range to `0..=255` with `0` at the `0x80` center,
+	/// both report down as positive so no axis is inverted.
+	/// Triggers are mapped from `0..=32767` to `0..=255`, the digital L2 and R2 bits are set when a trigger is not at rest.
+	/// The motion sensors and touch points are left neutral.
+	///
+	/// The buttons are mapped by position as follows:
+	///
+	/// | SDL                                 | DualShock4            |
+	/// |-------------------------------------|-----------------------|
+	/// | `Axis::LeftX` / `LeftY`             | `thumb_lx` / `thumb_ly` |
+	/// | `Axis::RightX` / `RightY`           | `thumb_rx` / `thumb_ry` |
+	/// | `Axis::TriggerLeft` / `TriggerRight` | L2 / R2              |
+	/// | `Button::A`                         | Cross                 |
+	/// | `Button::B`                         | Circle                |
+	/// | `Button::X`                         | Square                |
+	/// | `Button::Y`                         | Triangle              |
+	/// | `Button::LeftShoulder` / `RightShoulder` | L1 / R1          |
+	/// | `Button::LeftStick` / `RightStick`  | L3 / R3               |
+	/// | `Button::Back`                      | Share                 |
+	/// | `Button::Start`                     | Options               |
+	/// | `Button::Guide`                     | PS                    |
+	/// | `Button::Touchpad`                  | Touchpad click        |
+	/// | `Button::Misc1`                     | Mic mute              |
+	/// | `Button::DPad*`                     | D-Pad, including diagonals |
+	///
+	/// Paddles have no DualShock4 equivalent and are ignored.
+	/// Where your conventions differ, start from [`GameController::axis`] and [`GameController::button`] with a [`DS4ReportExBuilder`].
+	///
+	/// ```no_run
+	/// let sdl = sdl2::init().unwrap();
+	/// let controllers = sdl.game_controller().unwrap();
+	/// let controller = controllers.open(0).unwrap();
+	/// let mut events = sdl.event_pump().unwrap();
+	///
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::DualShock4Wired::new(client, vigem_client::TargetId::DUALSHOCK4_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// loop {
+	///     events.pump_events();
+	///     let report = vigem_client::DS4ReportEx::from_sdl2(&controller);
+	///     target.update_ex(&report).unwrap();
+	///     std::thread::sleep(std::time::Duration::from_millis(4));
+	/// }
+	/// ```
+	pub fn from_sdl2(controller: &GameController) -> Self {
+		let pressed = |button| controller.button(button);
+		let trigger_l = trigger_to_u8(controller.axis(Axis::TriggerLeft));
+		let trigger_r = trigger_to_u8(controller.axis(Axis::TriggerRight));
+
+		let buttons = DS4Buttons::new()
+			.cross(pressed(Button::A))
+			.circle(pressed(Button::B))
+			.square(pressed(Button::X))
+			.triangle(pressed(Button::Y))
+			.shoulder_left(pressed(Button::LeftShoulder))
+			.shoulder_right(pressed(Button::RightShoulder))
+			.trigger_left(trigger_l != 0)
+			.trigger_right(trigger_r != 0)
+			.thumb_left(pressed(Button::LeftStick))
+			.thumb_right(pressed(Button::RightStick))
+			.share(pressed(Button::Back))
+			.options(pressed(Button::Start))
+			.dpad(DpadDirection::from_buttons(
+				pressed(Button::DPadUp),
+				pressed(Button::DPadDown),
+				pressed(Button::DPadLeft),
+				pressed(Button::DPadRight),
+			));
+		let special = DS4SpecialButtons::new()
+			.ps_home(pressed(Button::Guide))
+			.touchpad(pressed(Button::Touchpad))
+			.mic_mute(pressed(Button::Misc1));
+
+		DS4ReportExBuilder::new()
+			.thumb_lx(stick_to_u8(controller.axis(Axis::LeftX)))
+			.thumb_ly(stick_to_u8(controller.axis(Axis::LeftY)))
+			.thumb_rx(stick_to_u8(controller.axis(Axis::RightX)))
+			.thumb_ry(stick_to_u8(controller.axis(Axis::RightY)))
+			.buttons(buttons)
+			.special(special)
+			.trigger_l(trigger_l)
+			.trigger_r(trigger_r)
+			.build()
+	}
+}
+
+impl From<&GameController> for DS4ReportEx {
+	#[inline]
+	fn from(controller: &GameController) -> Self {
+		DS4ReportEx::from_sdl2(controller)
+	}
+}
+
+impl XGamepad {
+	/// Creates a gamepad state from the polled state of an SDL [`GameController`].
+	///
+	/// Stick axes keep their `i16` range, the Y axes are inverted since XInput reports up as positive.
+	/// Triggers are mapped from `0..=32767` to `0..=255`.
+	///
+	/// The SDL button names follow the Xbox layout and map one to one:
+	///
+	/// | SDL                                 | Xbox360               |
+	/// |-------------------------------------|-----------------------|
+	/// | `Button::A` / `B` / `X` / `Y`       | A / B / X / Y         |
+	/// | `Button::LeftShoulder` / `RightShoulder` | LB / RB          |
+	/// | `Button::LeftStick` / `RightStick`  | LTHUMB / RTHUMB       |
+	/// | `Button::Back`                      | BACK                  |
+	/// | `Button::Start`                     | START                 |
+	/// | `Button::Guide`                     | GUIDE                 |
+	/// | `Button::DPad*`                     | UP / DOWN / LEFT / RIGHT |
+	///
+	/// The touchpad, misc and paddle buttons have no Xbox360 equivalent and are ignored.
+	/// See [`DS4ReportEx::from_sdl2`] for a worked example.
+	pub fn from_sdl2(controller: &GameController) -> Self {
+		let mapping = [
+			(Button::A, XButtons::A),
+			(Button::B, XButtons::B),
+			(Button::X, XButtons::X),
+			(Button::Y, XButtons::Y),
+			(Button::LeftShoulder, XButtons::LB),
+			(Button::RightShoulder, XButtons::RB),
+			(Button::LeftStick, XButtons::LTHUMB),
+			(Button::RightStick, XButtons::RTHUMB),
+			(Button::Back, XButtons::BACK),
+			(Button::Start, XButtons::START),
+			(Button::Guide, XButtons::GUIDE),
+			(Button::DPadUp, XButtons::UP),
+			(Button::DPadDown, XButtons::DOWN),
+			(Button::DPadLeft, XButtons::LEFT),
+			(Button::DPadRight, XButtons::RIGHT),
+		];
+		let raw = mapping.iter()
+			.filter(|&&(button, _)| controller.button(button))
+			.fold(0, |raw, &(_, flag)| raw | flag);
+
+		XGamepad {
+			buttons: XButtons { raw },
+			left_trigger: trigger_to_u8(controller.axis(Axis::TriggerLeft)),
+			right_trigger: trigger_to_u8(controller.axis(Axis::TriggerRight)),
+			thumb_lx: controller.axis(Axis::LeftX),
+			thumb_ly: invert_axis(controller.axis(Axis::LeftY)),
+			thumb_rx: controller.axis(Axis::RightX),
+			thumb_ry: invert_axis(controller.axis(Axis::RightY)),
+		}
+	}
+}
+
+impl From<&GameController> for XGamepad {
+	#[inline]
+	fn from(controller: &GameController) -> Self {
+		XGamepad::from_sdl2(controller)
+	}
+}
@@ -0,0 +1,31 @@
+// Logging macros for the `log` feature.
+//
+// With the feature enabled the macros forward to the log crate.
+// Without it they expand to dead code which only type checks the arguments, so nothing is formatted or evaluated.
+
+#[cfg(feature = "log")]
+macro_rules! log_debug {
+	($($arg:tt)+) => { ::log::debug!($($arg)+) };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! log_debug {
+	($($arg:tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_warn {
+	($($arg:tt)+) => { ::log::warn!($($arg)+) };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! log_warn {
+	($($arg:tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_error {
+	($($arg:tt)+) => { ::log::error!($($arg)+) };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! log_error {
+	($($arg:tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
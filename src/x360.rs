@@ -1,12 +1,49 @@
 use std::{fmt, mem, ptr};
-#[cfg(feature = "unstable_xtarget_notification")]
-use std::{marker, pin, thread};
+use std::{marker, pin, thread, time};
+use std::sync::atomic;
 use std::borrow::Borrow;
+use std::os::windows::io::RawHandle;
 use winapi::um::xinput::XINPUT_GAMEPAD;
 use winapi::shared::winerror;
+use winapi::shared::ntdef::HANDLE;
 use crate::*;
 
 /// XInput compatible button flags.
+///
+/// Buttons are set with the constants and the [`XButtons!`] macro, or in bulk from a mask with [`XButtons::from_bits`].
+/// The dpad buttons can be set from a [`DpadDirection`] with [`dpad`](XButtons::dpad).
+///
+/// # Bit layout
+///
+/// The raw representation, see [`XButtons::from_bits`] and [`XButtons::bits`], is a stable part of the API
+/// and matches `wButtons` of XInput's `XINPUT_GAMEPAD`:
+///
+/// | Bits | Meaning                                      |
+/// |------|----------------------------------------------|
+/// | 0    | Dpad up ([`XButtons::UP`])                   |
+/// | 1    | Dpad down ([`XButtons::DOWN`])               |
+/// | 2    | Dpad left ([`XButtons::LEFT`])               |
+/// | 3    | Dpad right ([`XButtons::RIGHT`])             |
+/// | 4    | Start ([`XButtons::START`])                  |
+/// | 5    | Back ([`XButtons::BACK`])                    |
+/// | 6    | Left thumb ([`XButtons::LTHUMB`])            |
+/// | 7    | Right thumb ([`XButtons::RTHUMB`])           |
+/// | 8    | Left shoulder ([`XButtons::LB`])             |
+/// | 9    | Right shoulder ([`XButtons::RB`])            |
+/// | 10   | Xbox guide ([`XButtons::GUIDE`])             |
+/// | 11   | Unused                                       |
+/// | 12   | A ([`XButtons::A`])                          |
+/// | 13   | B ([`XButtons::B`])                          |
+/// | 14   | X ([`XButtons::X`])                          |
+/// | 15   | Y ([`XButtons::Y`])                          |
+///
+/// ```
+/// use vigem_client::{DpadDirection, XButtons};
+///
+/// let buttons = XButtons::from_bits(XButtons::A | XButtons::LB).dpad(DpadDirection::SouthWest);
+/// assert_eq!(buttons.bits(), XButtons::A | XButtons::LB | XButtons::DOWN | XButtons::LEFT);
+/// assert_eq!(buttons.get_dpad(), DpadDirection::SouthWest);
+/// ```
 #[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
 #[repr(transparent)]
 pub struct XButtons {
@@ -80,6 +117,50 @@ impl XButtons {
 	pub const X: u16	  = 0x4000;
 	/// Y button.
 	pub const Y: u16	  = 0x8000;
+	/// Dpad button bits.
+	const DPAD_MASK: u16 = XButtons::UP | XButtons::DOWN | XButtons::LEFT | XButtons::RIGHT;
+
+	/// Creates the buttons from their raw representation, see the bit layout in the [type documentation](struct@XButtons).
+	#[inline]
+	pub const fn from_bits(bits: u16) -> XButtons {
+		XButtons { raw: bits }
+	}
+
+	/// Returns the raw representation of the buttons.
+	#[inline]
+	pub const fn bits(&self) -> u16 {
+		self.raw
+	}
+
+	/// Sets the dpad direction, replacing the previously set dpad buttons.
+	///
+	/// Diagonals press two dpad buttons.
+	#[inline]
+	pub fn dpad(mut self, direction: DpadDirection) -> XButtons {
+		let dpad = match direction {
+			DpadDirection::North => XButtons::UP,
+			DpadDirection::NorthEast => XButtons::UP | XButtons::RIGHT,
+			DpadDirection::East => XButtons::RIGHT,
+			DpadDirection::SouthEast => XButtons::DOWN | XButtons::RIGHT,
+			DpadDirection::South => XButtons::DOWN,
+			DpadDirection::SouthWest => XButtons::DOWN | XButtons::LEFT,
+			DpadDirection::West => XButtons::LEFT,
+			DpadDirection::NorthWest => XButtons::UP | XButtons::LEFT,
+			DpadDirection::None => 0,
+		};
+		self.raw = (self.raw & !XButtons::DPAD_MASK) | dpad;
+		self
+	}
+
+	/// Returns the dpad direction, opposite dpad buttons pressed together cancel each other out.
+	#[inline]
+	pub fn get_dpad(&self) -> DpadDirection {
+		DpadDirection::from_buttons(
+			self.raw & XButtons::UP != 0,
+			self.raw & XButtons::DOWN != 0,
+			self.raw & XButtons::LEFT != 0,
+			self.raw & XButtons::RIGHT != 0)
+	}
 }
 
 impl From<u16> for XButtons {
@@ -152,6 +233,29 @@ pub struct XGamepad {
 	pub thumb_ry: i16,
 }
 
+impl XGamepad {
+	/// A report with the sticks centered, the triggers released and no buttons pressed.
+	///
+	/// Equal to the default, usable in const contexts and as the base of a struct update:
+	///
+	/// ```
+	/// use vigem_client::XGamepad;
+	///
+	/// const PRESS_A: XGamepad = XGamepad { buttons: vigem_client::XButtons!(A), ..XGamepad::NEUTRAL };
+	/// assert_eq!(XGamepad::NEUTRAL, XGamepad::default());
+	/// assert_eq!(PRESS_A.thumb_lx, 0);
+	/// ```
+	pub const NEUTRAL: XGamepad = XGamepad {
+		buttons: XButtons { raw: 0 },
+		left_trigger: 0,
+		right_trigger: 0,
+		thumb_lx: 0,
+		thumb_ly: 0,
+		thumb_rx: 0,
+		thumb_ry: 0,
+	};
+}
+
 impl From<XINPUT_GAMEPAD> for XGamepad {
 	#[inline]
 	fn from(gamepad: XINPUT_GAMEPAD) -> Self {
@@ -177,59 +281,262 @@ impl AsMut<XINPUT_GAMEPAD> for XGamepad {
 	}
 }
 
-/// XInput notification structure.
-#[cfg(feature = "unstable_xtarget_notification")]
+/// A builder for [`XGamepad`].
+///
+/// Fields which are not set default to a neutral state: sticks centered at `0`, triggers at `0` and no buttons pressed.
+///
+/// ```
+/// use vigem_client::{DpadDirection, XGamepadBuilder};
+///
+/// let gamepad = XGamepadBuilder::new()
+///     .left_thumb_x(i16::MAX)
+///     .right_trigger(255)
+///     .a(true)
+///     .dpad(DpadDirection::NorthEast)
+///     .build();
+/// assert_eq!(gamepad.buttons, vigem_client::XButtons!(A | UP | RIGHT));
+/// assert_eq!(gamepad.thumb_lx, i16::MAX);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct XGamepadBuilder {
+	gamepad: XGamepad,
+}
+
+impl XGamepadBuilder {
+	/// Creates a builder with all fields in their neutral state.
+	#[inline]
+	pub fn new() -> Self {
+		XGamepadBuilder::default()
+	}
+
+	/// Sets the left thumb stick X axis.
+	#[inline]
+	pub fn left_thumb_x(mut self, value: i16) -> Self {
+		self.gamepad.thumb_lx = value;
+		self
+	}
+	/// Sets the left thumb stick Y axis, positive is up.
+	#[inline]
+	pub fn left_thumb_y(mut self, value: i16) -> Self {
+		self.gamepad.thumb_ly = value;
+		self
+	}
+	/// Sets the right thumb stick X axis.
+	#[inline]
+	pub fn right_thumb_x(mut self, value: i16) -> Self {
+		self.gamepad.thumb_rx = value;
+		self
+	}
+	/// Sets the right thumb stick Y axis, positive is up.
+	#[inline]
+	pub fn right_thumb_y(mut self, value: i16) -> Self {
+		self.gamepad.thumb_ry = value;
+		self
+	}
+	/// Sets the left trigger.
+	#[inline]
+	pub fn left_trigger(mut self, value: u8) -> Self {
+		self.gamepad.left_trigger = value;
+		self
+	}
+	/// Sets the right trigger.
+	#[inline]
+	pub fn right_trigger(mut self, value: u8) -> Self {
+		self.gamepad.right_trigger = value;
+		self
+	}
+
+	/// Sets all buttons at once, replacing any previously set buttons.
+	#[inline]
+	pub fn buttons(mut self, buttons: XButtons) -> Self {
+		self.gamepad.buttons = buttons;
+		self
+	}
+	/// Sets the state of the buttons in `mask`, see the [`XButtons`](struct@XButtons) constants.
+	#[inline]
+	pub fn button(mut self, mask: u16, pressed: bool) -> Self {
+		if pressed {
+			self.gamepad.buttons.raw |= mask;
+		}
+		else {
+			self.gamepad.buttons.raw &= !mask;
+		}
+		self
+	}
+	/// Sets the A button state.
+	#[inline]
+	pub fn a(self, pressed: bool) -> Self {
+		self.button(XButtons::A, pressed)
+	}
+	/// Sets the B button state.
+	#[inline]
+	pub fn b(self, pressed: bool) -> Self {
+		self.button(XButtons::B, pressed)
+	}
+	/// Sets the X button state.
+	#[inline]
+	pub fn x(self, pressed: bool) -> Self {
+		self.button(XButtons::X, pressed)
+	}
+	/// Sets the Y button state.
+	#[inline]
+	pub fn y(self, pressed: bool) -> Self {
+		self.button(XButtons::Y, pressed)
+	}
+	/// Sets the left shoulder button state.
+	#[inline]
+	pub fn lb(self, pressed: bool) -> Self {
+		self.button(XButtons::LB, pressed)
+	}
+	/// Sets the right shoulder button state.
+	#[inline]
+	pub fn rb(self, pressed: bool) -> Self {
+		self.button(XButtons::RB, pressed)
+	}
+	/// Sets the left thumb button state.
+	#[inline]
+	pub fn lthumb(self, pressed: bool) -> Self {
+		self.button(XButtons::LTHUMB, pressed)
+	}
+	/// Sets the right thumb button state.
+	#[inline]
+	pub fn rthumb(self, pressed: bool) -> Self {
+		self.button(XButtons::RTHUMB, pressed)
+	}
+	/// Sets the start button state.
+	#[inline]
+	pub fn start(self, pressed: bool) -> Self {
+		self.button(XButtons::START, pressed)
+	}
+	/// Sets the back button state.
+	#[inline]
+	pub fn back(self, pressed: bool) -> Self {
+		self.button(XButtons::BACK, pressed)
+	}
+	/// Sets the Xbox guide button state.
+	#[inline]
+	pub fn guide(self, pressed: bool) -> Self {
+		self.button(XButtons::GUIDE, pressed)
+	}
+
+	/// Sets the dpad direction, replacing any previously set dpad buttons.
+	///
+	/// Diagonals press two dpad buttons, see [`XButtons::dpad`].
+	#[inline]
+	pub fn dpad(mut self, direction: DpadDirection) -> Self {
+		self.gamepad.buttons = self.gamepad.buttons.dpad(direction);
+		self
+	}
+
+	/// Builds the gamepad.
+	#[inline]
+	pub fn build(self) -> XGamepad {
+		self.gamepad
+	}
+}
+
+impl From<XGamepadBuilder> for XGamepad {
+	#[inline]
+	fn from(builder: XGamepadBuilder) -> XGamepad {
+		builder.build()
+	}
+}
+
+/// Xbox360 output report received through notifications.
+///
+/// Contains the rumble motor speeds and the LED ring slot assigned by the game.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 #[repr(C)]
-pub struct XNotification {
+pub struct X360OutputReport {
 	pub large_motor: u8,
 	pub small_motor: u8,
 	pub led_number: u8,
 }
 
+/// XInput notification structure.
+#[deprecated(note = "renamed to X360OutputReport")]
+pub type XNotification = X360OutputReport;
+
 /// XInput notification request.
-#[cfg(feature = "unstable_xtarget_notification")]
 pub struct XRequestNotification {
 	client: Client,
 	xurn: bus::RequestNotification,
 	_unpin: marker::PhantomPinned,
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
 impl XRequestNotification {
 	/// Returns if the underlying target is still attached.
 	#[inline]
 	pub fn is_attached(&self) -> bool {
-		match self.xurn.buffer {
-			bus::RequestNotificationVariant::XUsb(ref buffer) => buffer.SerialNo != 0,
-			#[allow(unreachable_patterns)]
-			_ => unreachable!()
-		}
+		self.xurn.serial_no() != 0
+	}
+
+	/// Returns the event handle which is signaled when a requested notification completes.
+	///
+	/// The handle can be included in a custom `WaitForMultipleObjects` loop, after which the
+	/// notification is retrieved with [`poll(false)`](Self::poll).
+	/// The event is auto-reset, waiting on it consumes the signal and a subsequent `poll(true)` would block.
+	///
+	/// The handle is owned by this object and is only valid for as long as it is alive.
+	/// The caller must not close, signal or reset the handle.
+	#[inline]
+	pub fn event_handle(&self) -> RawHandle {
+		self.xurn.overlapped.hEvent
 	}
 
 	/// Spawns a thread to handle the notifications.
 	///
 	/// The callback `f` is invoked for every notification.
 	///
-	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread.
-	/// It is recommended to join the thread after the target from which the notifications are requested is dropped.
+	/// Returns a [`JoinHandle`](thread::JoinHandle) for the created thread and a [`NotificationStop`] handle to stop it.
+	/// It is recommended to join the thread after the target from which the notifications are requested is dropped,
+	/// or after [`NotificationStop::stop`] is called.
+	///
+	/// The thread returns `Ok(())` when it ends normally, because the target was unplugged or dropped or the thread was stopped.
+	/// It returns the error if polling failed for any other reason, which may warrant recreating the target.
+	///
+	/// A panic in the callback ends the thread, joining the thread returns the panic as an error.
+	/// Use [`spawn_thread_with_options`](Self::spawn_thread_with_options) to keep the thread running instead.
+	#[inline]
+	pub fn spawn_thread<F: FnMut(&XRequestNotification, X360OutputReport) + Send + 'static>(self, f: F) -> (thread::JoinHandle<Result<(), Error>>, NotificationStop) {
+		self.spawn_thread_with_options(SpawnOptions::new(), f)
+	}
+
+	/// Spawns a thread to handle the notifications with the given options.
+	///
+	/// See [`spawn_thread`](Self::spawn_thread) and [`SpawnOptions`].
 	#[inline]
-	pub fn spawn_thread<F: FnMut(&XRequestNotification, XNotification) + Send + 'static>(self, mut f: F) -> thread::JoinHandle<()> {
-		thread::spawn(move || {
+	pub fn spawn_thread_with_options<F: FnMut(&XRequestNotification, X360OutputReport) + Send + 'static>(self, options: SpawnOptions, mut f: F) -> (thread::JoinHandle<Result<(), Error>>, NotificationStop) {
+		let stop = NotificationStop::new();
+		let state = stop.state.clone();
+		let thread = options.clone().spawn(move || {
 			// Safety: the request notification object is not accessible after it is pinned
 			let mut reqn = self;
 			let mut reqn = unsafe { pin::Pin::new_unchecked(&mut reqn) };
+			// The pending request may be cancelled from another thread while the object is pinned here
+			let device = reqn.client.device;
+			let overlapped = unsafe { &mut reqn.as_mut().get_unchecked_mut().xurn.overlapped as *mut _ };
+			let _registration = state.register(device, overlapped);
 			loop {
-				reqn.as_mut().request();
+				{
+					// Checking the flag and issuing the request must not race with `stop`
+					let _pending = state.lock();
+					if state.stopped.load(atomic::Ordering::SeqCst) {
+						break Ok(());
+					}
+					reqn.as_mut().request();
+				}
 				let result = reqn.as_mut().poll(true);
 				match result {
 					Ok(None) => {},
-					Ok(Some(data)) => f(&reqn, data),
-					// When the target is dropped the notification request is aborted
-					Err(_) => break,
+					Ok(Some(data)) => options.invoke(|| f(&reqn, data)),
+					// When the target is dropped or the thread is stopped the notification request is aborted
+					Err(Error::OperationAborted) => break Ok(()),
+					Err(err) => break Err(err),
 				}
 			}
-		})
+		});
+		(thread, stop)
 	}
 
 	/// Requests a notification.
@@ -238,14 +545,8 @@ impl XRequestNotification {
 		unsafe {
 			let device = self.client.device;
 			let xurn = &mut self.get_unchecked_mut().xurn;
-			match xurn.buffer {
-				bus::RequestNotificationVariant::XUsb(ref mut buffer) => {
-					if buffer.SerialNo != 0 {
-						xurn.ioctl(device);
-					}
-				},
-				#[allow(unreachable_patterns)]
-				_ => unreachable!()
+			if xurn.serial_no() != 0 {
+				xurn.ioctl(device);
 			}
 		}
 	}
@@ -257,80 +558,84 @@ impl XRequestNotification {
 	///
 	/// Returns:
 	///
-	/// * `Ok(None)`: When `wait` is false and there is no notification yet.
+	/// * `Ok(None)`: When `wait` is false and there is no notification yet, the request is still pending.
 	/// * `Ok(Some(_))`: The notification was successfully received.  
 	///   Another request should be made or any other calls to `poll` return the same result.
 	/// * `Err(OperationAborted)`: The underlying target was unplugged causing any pending notification requests to abort.
-	/// * `Err(_)`: An unexpected error occurred.
+	/// * `Err(_)`: An unexpected error occurred, including the last [`request`](Self::request) failing to start.
+	#[inline(never)]
+	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<X360OutputReport>, Error> {
+		self.poll_with(|xurn, device| unsafe { xurn.poll(device, wait) })
+	}
+
+	/// Polls the request for notifications, waiting at most `timeout` for a notification to arrive.
+	///
+	/// The timeout is rounded up to whole milliseconds, a non-zero sub-millisecond timeout waits for at least one millisecond.
+	/// Timeouts larger than `u32::MAX` milliseconds wait indefinitely.
+	///
+	/// Returns the same results as [`poll`](Self::poll), where `Ok(None)` means the timeout elapsed without a notification.
 	#[inline(never)]
-	pub fn poll(self: pin::Pin<&mut Self>, wait: bool) -> Result<Option<XNotification>, Error> {
+	pub fn poll_timeout(self: pin::Pin<&mut Self>, timeout: time::Duration) -> Result<Option<X360OutputReport>, Error> {
+		let timeout_ms = event::timeout_ms(timeout);
+		self.poll_with(|xurn, device| unsafe { xurn.poll_timeout(device, timeout_ms) })
+	}
+
+	#[inline]
+	fn poll_with<F: FnOnce(&mut bus::RequestNotification, HANDLE) -> Result<(), u32>>(self: pin::Pin<&mut Self>, f: F) -> Result<Option<X360OutputReport>, Error> {
 		unsafe {
 			let device = self.client.device;
 			let xurn = &mut self.get_unchecked_mut().xurn;
-			match xurn.poll(device, wait) {
+			match f(xurn, device) {
 				Ok(()) => {
 					match &xurn.buffer {
-						bus::RequestNotificationVariant::XUsb(buffer) => {
-							Ok(Some(XNotification {
+						bus::RequestNotificationVariant::X360(buffer) => {
+							Ok(Some(X360OutputReport {
 								large_motor: buffer.LargeMotor,
 								small_motor: buffer.SmallMotor,
 								led_number: buffer.LedNumber,
 							}))
 						},
-						#[allow(unreachable_patterns)]
-						_ => unreachable!()
+						bus::RequestNotificationVariant::DS4(_) => Err(Error::InternalVariantMismatch),
 					}
 				}
-				Err(winerror::ERROR_IO_INCOMPLETE) => Ok(None),
+				// The request is still pending
+				Err(winerror::ERROR_IO_INCOMPLETE) | Err(winerror::ERROR_IO_PENDING) => Ok(None),
 				Err(winerror::ERROR_OPERATION_ABORTED) => {
 					// Operation was aborted, fail all future calls
 					// The is aborted when the underlying target is unplugged
 					// This has the potential for a race condition:
 					//  What happens if a new target is plugged inbetween calls to poll and request...
-					match xurn.buffer {
-						bus::RequestNotificationVariant::XUsb(ref mut buffer) => { buffer.SerialNo = 0; },
-						#[allow(unreachable_patterns)]
-						_ => unreachable!()
-					}
+					log_debug!("notification request for serial number {} aborted", xurn.serial_no());
+					xurn.clear_serial_no();
 					Err(Error::OperationAborted)
 				},
-				Err(err) => Err(Error::WinError(err)),
+				Err(err) => {
+					let err = Error::win(err, Operation::Notify);
+					log_warn!("notification request for serial number {} failed: {}", xurn.serial_no(), err);
+					Err(err)
+				},
 			}
 		}
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
 unsafe impl Sync for XRequestNotification {}
-#[cfg(feature = "unstable_xtarget_notification")]
 unsafe impl Send for XRequestNotification {}
 
-#[cfg(feature = "unstable_xtarget_notification")]
 impl fmt::Debug for XRequestNotification {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		let buffer = match &self.xurn.buffer {
-			bus::RequestNotificationVariant::XUsb(buffer) => buffer,
-			#[allow(unreachable_patterns)]
-			_ => unreachable!()
-		};
 		f.debug_struct("XRequestNotification")
 			.field("client", &format_args!("{:?}", self.client))
-			.field("serial_no", &buffer.SerialNo)
+			.field("serial_no", &self.xurn.serial_no())
 			.finish()
 	}
 }
 
-#[cfg(feature = "unstable_xtarget_notification")]
 impl Drop for XRequestNotification {
 	fn drop(&mut self) {
 		unsafe {
 			let this = pin::Pin::new_unchecked(self);
-			let serial_no = match &this.xurn.buffer {
-				bus::RequestNotificationVariant::XUsb(buffer) => buffer.SerialNo,
-				#[allow(unreachable_patterns)]
-				_ => unreachable!()
-			};
-			if serial_no != 0 {
+			if this.xurn.serial_no() != 0 {
 				let device = this.client.device;
 				let xurn = &mut this.get_unchecked_mut().xurn;
 				let _ = xurn.cancel(device);
@@ -345,7 +650,7 @@ pub type XTarget = Xbox360Wired<Client>;
 /// A virtual Microsoft Xbox 360 Controller (wired).
 pub struct Xbox360Wired<CL: Borrow<Client>> {
 	client: CL,
-	event: Event,
+	event: TargetEvent,
 	serial_no: u32,
 	id: TargetId,
 }
@@ -354,7 +659,7 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 	/// Creates a new instance.
 	#[inline]
 	pub fn new(client: CL, id: TargetId) -> Xbox360Wired<CL> {
-		let event = Event::new(false, false);
+		let event = TargetEvent::new(client.borrow().shared_event.as_ref(), EventMode::AutoReset);
 		Xbox360Wired { client, event, serial_no: 0, id }
 	}
 
@@ -370,6 +675,20 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		self.id
 	}
 
+	/// Returns the event handle used for the requests made by this controller.
+	///
+	/// The event is auto-reset and is signaled whenever a request issued by this controller completes.
+	/// It can be included in a custom `WaitForMultipleObjects` loop, but must not be waited on
+	/// while a method of this controller is running as that would consume the signal the method is waiting for.
+	///
+	/// The handle is owned by the controller and is only valid for as long as the controller is alive.
+	/// The caller must not close, signal or reset the handle.
+	/// When the client is in [shared event mode](Client::shared_event_mode) the event is shared with all the targets of the client.
+	#[inline]
+	pub fn event_handle(&self) -> RawHandle {
+		self.event.handle()
+	}
+
 	/// Returns the client.
 	#[inline]
 	pub fn client(&self) -> &CL {
@@ -390,24 +709,71 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 	}
 
 	/// Plugs the controller in.
+	///
+	/// The search for a free slot starts after the slot last handed out by the client,
+	/// or at the lowest slot unplugged since, see [`plugin_hint`](Self::plugin_hint).
 	#[inline(never)]
 	pub fn plugin(&mut self) -> Result<(), Error> {
+		self.plugin_from(None)
+	}
+
+	/// Plugs the controller in, starting the search for a free slot at `start_serial`.
+	///
+	/// The driver has to be asked for every serial number in turn until a free one is found,
+	/// with many targets plugged in starting near a free slot avoids most of these requests.
+	/// The search wraps around to serial number 1 so any free slot is still found.
+	#[inline(never)]
+	pub fn plugin_hint(&mut self, start_serial: u32) -> Result<(), Error> {
+		self.plugin_from(Some(start_serial))
+	}
+
+	#[inline]
+	fn plugin_from(&mut self, start: Option<u32>) -> Result<(), Error> {
 		if self.is_attached() {
 			return Err(Error::AlreadyConnected);
 		}
 
 		let mut plugin = bus::PluginTarget::x360_wired(1, self.id.vendor, self.id.product);
-		let device = self.client.borrow().device;
+		let client = self.client.borrow();
+		let event = self.event.lock();
+		self.serial_no = client.plug_unique(self.id, || client.scan_slots(start, None, |serial_no| {
+			plugin.SerialNo = serial_no;
+			unsafe { plugin.ioctl(client.device, event.handle) }.is_ok()
+		}))?;
 
-		// Yes this is how the driver is implemented
-		while unsafe { plugin.ioctl(device, self.event.handle) }.is_err() {
-			plugin.SerialNo += 1;
-			if plugin.SerialNo >= u16::MAX as u32 {
-				return Err(Error::NoFreeSlot);
-			}
+		Ok(())
+	}
+
+	/// Plugs the controller in with the given serial number.
+	///
+	/// Unlike [`plugin`](Self::plugin) no free slot is searched for, only `serial_no` is tried.
+	/// This makes the serial number deterministic, eg. to reattach to a known slot.
+	///
+	/// Returns [`Error::SerialInUse`] if the serial number is taken by another target.
+	/// Serial numbers must be in the range `1..=65535`, other values return `WinError` with `ERROR_INVALID_PARAMETER`.
+	/// Other failures of the plugin request, eg. after a driver restart, are returned as `WinError` with [`Operation::Plugin`].
+	#[inline(never)]
+	pub fn plugin_with_serial(&mut self, serial_no: u32) -> Result<(), Error> {
+		if self.is_attached() {
+			return Err(Error::AlreadyConnected);
+		}
+		if serial_no == 0 || serial_no > u16::MAX as u32 {
+			return Err(Error::win(winerror::ERROR_INVALID_PARAMETER, Operation::Plugin));
 		}
 
-		self.serial_no = plugin.SerialNo;
+		let mut plugin = bus::PluginTarget::x360_wired(serial_no, self.id.vendor, self.id.product);
+		let client = self.client.borrow();
+		let event = self.event.lock();
+		client.plug_unique(self.id, || {
+			match unsafe { plugin.ioctl(client.device, event.handle) } {
+				Ok(()) => Ok(serial_no),
+				// ViGEmBus reports a serial number which is already present as STATUS_INVALID_PARAMETER
+				Err(winerror::ERROR_INVALID_PARAMETER) => Err(Error::SerialInUse),
+				Err(err) => Err(Error::win(err, Operation::Plugin)),
+			}
+		})?;
+
+		self.serial_no = serial_no;
 		Ok(())
 	}
 
@@ -418,12 +784,18 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 			return Err(Error::NotPluggedIn);
 		}
 
-		unsafe {
+		let result = unsafe {
 			let mut unplug = bus::UnplugTarget::new(self.serial_no);
 			let device = self.client.borrow().device;
-			unplug.ioctl(device, self.event.handle)?;
+			unplug.ioctl(device, self.event.lock().handle).map_err(|err| Error::win(err, Operation::Unplug))
+		};
+		if let Err(err) = result {
+			log_warn!("unplugging target with serial number {} failed: {}", self.serial_no, err);
+			return Err(err);
 		}
 
+		log_debug!("unplugged target with serial number {}", self.serial_no);
+		self.client.borrow().target_removed(self.id, Some(self.serial_no));
 		self.serial_no = 0;
 		Ok(())
 	}
@@ -440,13 +812,64 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		unsafe {
 			let mut wait = bus::WaitDeviceReady::new(self.serial_no);
 			let device = self.client.borrow().device;
-			wait.ioctl(device, self.event.handle)?;
+			wait.ioctl(device, self.event.lock().handle).map_err(|err| Error::win(err, Operation::WaitReady))?;
 		}
 
 		Ok(())
 	}
 
+	/// Waits until the virtual controller is ready without blocking the async runtime.
+	///
+	/// The readiness request is run on the blocking thread pool of the tokio runtime with [`tokio::task::spawn_blocking`],
+	/// using a duplicate of the client's handle. Must be called from within a tokio runtime.
+	///
+	/// Returns the same errors as [`wait_ready`](Self::wait_ready),
+	/// and [`Error::OperationAborted`] if the runtime shuts down while waiting.
+	/// Dropping the future before it completes leaves the request running on the blocking thread pool until the controller is ready or unplugged.
+	#[cfg(feature = "tokio")]
+	pub async fn wait_ready_async(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		self.client.borrow().wait_ready_async(self.serial_no).await
+	}
+
+	/// Returns the XInput user index assigned to the controller, the player slot in the range `0..4`.
+	///
+	/// Windows assigns the user index once an application opens the controller through XInput,
+	/// until then and when all four slots are taken `Ok(None)` is returned.
+	///
+	/// ```no_run
+	/// let client = vigem_client::Client::connect().unwrap();
+	/// let mut target = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+	/// target.plugin().unwrap();
+	/// target.wait_ready().unwrap();
+	///
+	/// if let Some(index) = target.user_index().unwrap() {
+	///     println!("your virtual pad is player {}", index + 1);
+	/// }
+	/// ```
+	#[inline(never)]
+	pub fn user_index(&self) -> Result<Option<u8>, Error> {
+		if !self.is_attached() {
+			return Err(Error::NotPluggedIn);
+		}
+
+		unsafe {
+			let mut gui = bus::XUsbGetUserIndex::new(self.serial_no);
+			let device = self.client.borrow().device;
+			match gui.ioctl(device, self.event.lock().handle) {
+				Ok(()) if gui.UserIndex < 4 => Ok(Some(gui.UserIndex as u8)),
+				Ok(()) | Err(winerror::ERROR_INVALID_DEVICE_OBJECT_PARAMETER) => Ok(None),
+				Err(err) => Err(Error::win(err, Operation::Other)),
+			}
+		}
+	}
+
 	/// Gets the user index of the device in XInput.
+	///
+	/// Returns [`Error::UserIndexOutOfRange`] if no user index is assigned, see [`user_index`](Self::user_index).
 	#[inline(never)]
 	pub fn get_user_index(&mut self) -> Result<u32, Error> {
 		if !self.is_attached() {
@@ -456,11 +879,11 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		let user_index = unsafe {
 			let mut gui = bus::XUsbGetUserIndex::new(self.serial_no);
 			let device = self.client.borrow().device;
-			match gui.ioctl(device, self.event.handle) {
+			match gui.ioctl(device, self.event.lock().handle) {
 				Ok(()) => (),
 				// Err(winerror::ERROR_ACCESS_DENIED) => return Err(Error::InvalidTarget),
 				Err(winerror::ERROR_INVALID_DEVICE_OBJECT_PARAMETER) => return Err(Error::UserIndexOutOfRange),
-				Err(err) => return Err(Error::WinError(err)),
+				Err(err) => return Err(Error::win(err, Operation::Other)),
 			}
 
 			gui.UserIndex
@@ -469,6 +892,21 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		Ok(user_index)
 	}
 
+	/// Resets the virtual controller to its neutral state.
+	///
+	/// Submits a report with the thumb sticks centered at `0`, the triggers at `0` and no buttons pressed,
+	/// eg. before unplugging so applications do not keep the last input as held.
+	/// Rumble is set by the applications and is not affected.
+	///
+	/// Does nothing if the controller is not plugged in.
+	#[inline(never)]
+	pub fn reset(&mut self) -> Result<(), Error> {
+		if !self.is_attached() {
+			return Ok(());
+		}
+		self.update(&XGamepad::default())
+	}
+
 	/// Updates the virtual controller state.
 	#[inline(never)]
 	pub fn update(&mut self, gamepad: &XGamepad) -> Result<(), Error> {
@@ -479,10 +917,14 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		unsafe {
 			let mut xsr = bus::XUsbSubmitReport::new(self.serial_no, *gamepad);
 			let device = self.client.borrow().device;
-			match xsr.ioctl(device, self.event.handle) {
+			match xsr.ioctl(device, self.event.lock().handle) {
 				Ok(()) => Ok(()),
 				Err(winerror::ERROR_DEV_NOT_EXIST) => Err(Error::TargetNotReady),
-				Err(err) => Err(Error::WinError(err)),
+				Err(err) => {
+					let err = Error::win(err, Operation::Submit);
+					log_warn!("submitting a report to target with serial number {} failed: {}", self.serial_no, err);
+					Err(err)
+				},
 			}
 		}
 	}
@@ -493,7 +935,8 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 	///
 	/// Do not create more than one request notification per target.
 	/// Notifications may get lost or received by one or more listeners.
-	#[cfg(feature = "unstable_xtarget_notification")]
+	///
+	/// The client's handle is duplicated for the notification request, see [`Client::try_clone`] for the errors this may return.
 	#[inline(never)]
 	pub fn request_notification(&mut self) -> Result<XRequestNotification, Error> {
 		if !self.is_attached() {
@@ -501,7 +944,7 @@ impl<CL: Borrow<Client>> Xbox360Wired<CL> {
 		}
 
 		let client = self.client.borrow().try_clone()?;
-		let xurn = bus::RequestNotification::new(bus::RequestNotificationVariant::XUsb(bus::XUsbRequestNotification::new(self.serial_no)));
+		let xurn = bus::RequestNotification::new(bus::RequestNotificationVariant::X360(bus::XUsbRequestNotification::new(self.serial_no)));
 
 		Ok(XRequestNotification { client, xurn, _unpin: marker::PhantomPinned })
 	}
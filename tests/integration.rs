@@ -30,3 +30,273 @@ fn target_not_ready() {
 
 	// assert_eq!(result, Err(vigem::Error::TargetNotReady));
 }
+
+#[test]
+fn shared_client() {
+	let client = std::sync::Arc::new(vigem::Client::connect().unwrap());
+
+	let mut targets: Vec<_> = (0..8).map(|_| {
+		let mut target = vigem::DualShock4Wired::new(client.clone(), vigem::TargetId::DUALSHOCK4_WIRED);
+		target.plugin().unwrap();
+		target.wait_ready().unwrap();
+		target
+	}).collect();
+
+	let notifications: Vec<_> = targets.iter_mut()
+		.map(|target| target.request_notification_shared().unwrap())
+		.collect();
+
+	for target in &mut targets {
+		assert_eq!(target.update(&vigem::DS4Report::default()), Ok(()));
+	}
+
+	drop(notifications);
+	drop(targets);
+	assert_eq!(std::sync::Arc::strong_count(&client), 1);
+}
+
+#[test]
+fn plugin_with_serial() {
+	let client = vigem::Client::connect().unwrap();
+	let mut first = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	let mut second = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+
+	first.plugin().unwrap();
+	let serial_no = first.serial_no().unwrap();
+
+	assert_eq!(second.plugin_with_serial(serial_no), Err(vigem::Error::SerialInUse));
+	first.unplug().unwrap();
+	assert_eq!(second.plugin_with_serial(serial_no), Ok(()));
+}
+
+#[test]
+fn plugin_hint() {
+	let client = vigem::Client::connect().unwrap();
+	let mut first = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	let mut second = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+
+	first.plugin_hint(1000).unwrap();
+	let serial_no = first.serial_no().unwrap();
+	assert!(serial_no >= 1000);
+
+	// The freed slot is reused by the next scan
+	first.unplug().unwrap();
+	second.plugin().unwrap();
+	assert_eq!(second.serial_no(), Some(serial_no));
+}
+
+#[test]
+fn notification_request_poll_stress() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let mut notification = Box::pin(target.request_notification().unwrap());
+	for _ in 0..1000 {
+		// Requests, polls without blocking and cancels the pending request
+		assert!(notification.as_mut().drain().is_ok());
+	}
+
+	notification.as_mut().request();
+	for _ in 0..1000 {
+		// Still pending, nobody sends output reports
+		assert_eq!(notification.as_mut().poll(false), Ok(None));
+	}
+}
+
+#[test]
+fn plugin_ds4_revisions() {
+	let client = vigem::Client::connect().unwrap();
+	for &id in &[vigem::TargetId::DUALSHOCK4_V1, vigem::TargetId::DUALSHOCK4_V2] {
+		let mut target = vigem::DualShock4Wired::new(&client, id);
+		assert_eq!(target.plugin(), Ok(()));
+		assert_eq!(target.wait_ready(), Ok(()));
+		assert_eq!(target.id(), id);
+	}
+}
+
+#[test]
+fn reject_duplicate_ids() {
+	let client = vigem::Client::connect().unwrap().reject_duplicate_ids();
+	let mut first = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	let mut second = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	let mut other = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_V2);
+
+	assert_eq!(first.plugin(), Ok(()));
+	assert_eq!(second.plugin(), Err(vigem::Error::DuplicateTargetId));
+	assert_eq!(other.plugin(), Ok(()));
+
+	// The id is released when unplugged
+	first.unplug().unwrap();
+	assert_eq!(second.plugin(), Ok(()));
+}
+
+#[test]
+fn read_output() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.read_output(), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	for _ in 0..1000 {
+		// Nobody sends output reports, every call cancels its request again
+		assert_eq!(target.read_output(), Ok(None));
+	}
+}
+
+#[test]
+fn manual_reset_event() {
+	let client = vigem::Client::connect().unwrap();
+	let id = vigem::TargetId::DUALSHOCK4_WIRED;
+	let mut target = vigem::DualShock4Wired::with_event_mode(&client, id, vigem::EventMode::ManualReset);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	for _ in 0..100 {
+		assert_eq!(target.update(&vigem::DS4Report::default()), Ok(()));
+	}
+}
+
+#[test]
+fn can_plugin() {
+	let client = vigem::Client::connect().unwrap().reject_duplicate_ids();
+	let id = vigem::TargetId::DUALSHOCK4_WIRED;
+	assert_eq!(client.can_plugin(id), Ok(true));
+
+	let mut target = vigem::DualShock4Wired::new(&client, id);
+	target.plugin().unwrap();
+	assert_eq!(client.can_plugin(id), Ok(false));
+	assert_eq!(client.can_plugin(vigem::TargetId::DUALSHOCK4_V2), Ok(true));
+
+	target.unplug().unwrap();
+	assert_eq!(client.can_plugin(id), Ok(true));
+}
+
+#[test]
+fn notification_set() {
+	let client = vigem::Client::connect().unwrap();
+	let mut first = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	let mut second = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	first.plugin().unwrap();
+	second.plugin().unwrap();
+
+	let mut set = vigem::NotificationSet::new();
+	assert_eq!(set.poll_any(std::time::Duration::from_millis(10)), Err(vigem::Error::NotPluggedIn));
+	assert_eq!(set.push(first.request_notification().unwrap()).unwrap(), 0);
+	assert_eq!(set.push(second.request_notification().unwrap()).unwrap(), 1);
+	assert_eq!(set.poll_any(std::time::Duration::from_millis(10)), Err(vigem::Error::Timeout));
+
+	// Unplugging one target aborts its request without affecting the other
+	first.unplug().unwrap();
+	assert_eq!(set.poll_any(std::time::Duration::from_millis(10)), Err(vigem::Error::Timeout));
+	assert!(!set.get(0).unwrap().is_attached());
+	assert!(set.get(1).unwrap().is_attached());
+
+	second.unplug().unwrap();
+	assert_eq!(set.poll_any(std::time::Duration::from_millis(10)), Err(vigem::Error::NotPluggedIn));
+}
+
+#[test]
+fn packet_counter_wraps() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	assert_eq!(target.packet_counter(), None);
+
+	target.set_packet_counter(Some(vigem::DualShock4Wired::<&vigem::Client>::PACKET_COUNTER_MAX));
+	target.update_ex(&vigem::DS4ReportEx::default()).unwrap();
+	assert_eq!(target.packet_counter(), Some(0));
+
+	target.update_raw(vigem::DS4ReportEx::default().as_bytes()).unwrap();
+	assert_eq!(target.packet_counter(), Some(0));
+
+	target.set_packet_counter(None);
+	target.update_ex(&vigem::DS4ReportEx::default()).unwrap();
+	assert_eq!(target.packet_counter(), None);
+}
+
+#[test]
+fn used_serials() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	target.plugin().unwrap();
+	let serial_no = target.serial_no().unwrap();
+
+	assert!(client.used_serials().unwrap().contains(&serial_no));
+	target.unplug().unwrap();
+	assert!(!client.used_serials().unwrap().contains(&serial_no));
+}
+
+#[test]
+fn update_in_place() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.update_in_place(|report| report.set_trigger_l(255)), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	target.update_in_place(|report| report.set_thumb_lx(0)).unwrap();
+	target.update_in_place(|report| report.set_trigger_r(255)).unwrap();
+}
+
+#[test]
+fn cycle() {
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_eq!(target.cycle(std::time::Duration::ZERO), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+	let serial_no = target.serial_no();
+	target.cycle(std::time::Duration::from_millis(100)).unwrap();
+	assert!(target.is_ready());
+	assert_eq!(target.serial_no(), serial_no);
+	target.update(&vigem::DS4Report::default()).unwrap();
+}
+
+#[test]
+fn connect_with() {
+	let client = vigem::Client::connect_with(vigem::ConnectOptions::new()).unwrap();
+	let second = vigem::Client::connect_with(vigem::ConnectOptions::default()).unwrap();
+	assert!(client.is_valid());
+	assert!(second.is_valid());
+}
+
+#[test]
+fn raw_ioctl() {
+	const IOCTL_CHECK_VERSION: u32 = 0x2AA00C;
+	let mut input = [0u8; 8];
+	input[..4].copy_from_slice(&8u32.to_le_bytes());
+	input[4..].copy_from_slice(&0x0001u32.to_le_bytes());
+
+	let client = vigem::Client::connect().unwrap();
+	assert_eq!(unsafe { client.raw_ioctl(IOCTL_CHECK_VERSION, &input, &mut []) }, Ok(0));
+	assert!(unsafe { client.raw_ioctl(IOCTL_CHECK_VERSION, &input[..4], &mut []) }.is_err());
+}
+
+#[test]
+fn update_async() {
+	fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+	let client = vigem::Client::connect().unwrap();
+	let mut target = vigem::DualShock4Wired::new(&client, vigem::TargetId::DUALSHOCK4_WIRED);
+	assert_send_sync(&target);
+	assert_eq!(target.update_async(&vigem::DS4Report::default(), |_| panic!()), Err(vigem::Error::NotPluggedIn));
+
+	target.plugin().unwrap();
+	target.wait_ready().unwrap();
+
+	let (sender, receiver) = std::sync::mpsc::channel();
+	for index in 0..4u8 {
+		let sender = sender.clone();
+		let report = vigem::DS4ReportBuilder::new().trigger_r(index).build();
+		target.update_async(&report, move |result| sender.send((index, result)).unwrap()).unwrap();
+	}
+	target.poll_updates(true);
+	assert_eq!(target.pending_updates(), 0);
+
+	let results: Vec<_> = receiver.try_iter().collect();
+	assert_eq!(results, [(0, Ok(())), (1, Ok(())), (2, Ok(())), (3, Ok(()))]);
+}
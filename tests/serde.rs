@@ -0,0 +1,49 @@
+#![cfg(feature = "serde")]
+
+use vigem_client::*;
+
+#[test]
+fn report_ex_roundtrip() {
+	let report = DS4ReportExBuilder::new()
+		.thumb_lx(0x12)
+		.thumb_ry(0xF0)
+		.buttons(DS4Buttons::new().cross(true).dpad(DpadDirection::SouthWest))
+		.special(DS4SpecialButtons::new().ps_home(true))
+		.trigger_r(200)
+		.gyro_y(-1234)
+		.accel_z(4321)
+		.status(DS4Status::with_battery_status(BatteryStatus::Charging(7)))
+		.touch_reports(Some(DS4TouchReport::new(3, Some(DS4TouchPoint::new(1234, 567)), None)), None, None)
+		.build();
+
+	let json = serde_json::to_string(&report).unwrap();
+	assert!(json.contains("\"thumb_lx\":18"));
+	let result: DS4ReportEx = serde_json::from_str(&json).unwrap();
+	assert_eq!(result, report);
+}
+
+#[test]
+fn report_roundtrip() {
+	let report = DS4ReportBuilder::new()
+		.thumb_rx(0x40)
+		.buttons(DS4Buttons::new().square(true))
+		.trigger_l(100)
+		.build();
+
+	let json = serde_json::to_string(&report).unwrap();
+	let result: DS4Report = serde_json::from_str(&json).unwrap();
+	assert_eq!(result, report);
+}
+
+#[test]
+fn input_sequence_roundtrip() {
+	let start = std::time::Instant::now();
+	let mut sequence = InputSequence::new();
+	sequence.push(start, DS4ReportExBuilder::new().buttons(DS4Buttons::new().cross(true)).build());
+	sequence.push(start + std::time::Duration::from_millis(16), DS4ReportExBuilder::new().trigger_l(255).build());
+
+	let json = serde_json::to_string(&sequence).unwrap();
+	let result: InputSequence = serde_json::from_str(&json).unwrap();
+	assert_eq!(result.frames(), sequence.frames());
+	assert_eq!(result.duration(), std::time::Duration::from_millis(16));
+}